@@ -0,0 +1,320 @@
+use apolaki_canvas::Canvas;
+use apolaki_matrix::{BaseMatrix, Invert};
+use apolaki_ray::Ray;
+use apolaki_tuple::{point, Tuple};
+use apolaki_world::World;
+
+use crate::render::RenderOptions;
+
+// orients the world relative to an eye at `from`, looking at `to`
+pub fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> BaseMatrix<4> {
+    let forward = (to - from).normalize();
+    let left = forward.cross(up.normalize());
+    let true_up = left.cross(forward);
+
+    let orientation = BaseMatrix::from([
+        [left.x(), left.y(), left.z(), 0.],
+        [true_up.x(), true_up.y(), true_up.z(), 0.],
+        [-forward.x(), -forward.y(), -forward.z(), 0.],
+        [0., 0., 0., 1.],
+    ]);
+
+    orientation * BaseMatrix::identity().translate(-from.x(), -from.y(), -from.z())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub hsize: usize,
+    pub vsize: usize,
+    pub field_of_view: f64,
+    pub transform: BaseMatrix<4>,
+    // diameter of the lens; 0 is a pinhole, with everything in focus
+    pub aperture: f64,
+    // distance from the lens to the plane that is in perfect focus
+    pub focal_distance: f64,
+}
+
+impl Camera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        Self {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: BaseMatrix::identity(),
+            aperture: 0.,
+            focal_distance: 1.,
+        }
+    }
+
+    // half the width and height of the canvas, one unit in front of the camera
+    fn half_extents(&self) -> (f64, f64) {
+        let half_view = (self.field_of_view / 2.).tan();
+        let aspect = self.hsize as f64 / self.vsize as f64;
+
+        if aspect >= 1. {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        }
+    }
+
+    pub fn pixel_size(&self) -> f64 {
+        let (half_width, _) = self.half_extents();
+        (half_width * 2.) / self.hsize as f64
+    }
+
+    // a ray through the center of the pixel
+    pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
+        self.ray_for_subpixel(px, py, 0.5, 0.5)
+    }
+
+    // a ray through the pixel at `dx`, `dy`, where (0, 0) is its top-left corner and
+    // (1, 1) its bottom-right
+    pub fn ray_for_subpixel(&self, px: usize, py: usize, dx: f64, dy: f64) -> Ray {
+        let (world_x, world_y) = self.canvas_point(px, py, dx, dy);
+
+        let inverse = self.transform.invert();
+        let pixel = inverse * point(world_x, world_y, -1);
+        let origin = inverse * point(0, 0, 0);
+        let direction = (pixel - origin).normalize();
+
+        Ray::new(origin, direction)
+    }
+
+    // Like `ray_for_subpixel`, but starting from `lens`, a point on the unit disk that is
+    // scaled to the aperture. All rays through a pixel meet on the focal plane, so only
+    // objects on that plane are sharp.
+    pub fn ray_through_lens(
+        &self,
+        px: usize,
+        py: usize,
+        dx: f64,
+        dy: f64,
+        lens: (f64, f64),
+    ) -> Ray {
+        let (world_x, world_y) = self.canvas_point(px, py, dx, dy);
+        let radius = self.aperture / 2.;
+        let d = self.focal_distance;
+
+        let inverse = self.transform.invert();
+        let focal_point = inverse * point(world_x * d, world_y * d, -d);
+        let origin = inverse * point(lens.0 * radius, lens.1 * radius, 0);
+        let direction = (focal_point - origin).normalize();
+
+        Ray::new(origin, direction)
+    }
+
+    // where the sample at `dx`, `dy` within the pixel lies on the canvas, in camera space
+    fn canvas_point(&self, px: usize, py: usize, dx: f64, dy: f64) -> (f64, f64) {
+        let (half_width, half_height) = self.half_extents();
+        let pixel_size = self.pixel_size();
+
+        // offset from the edge of the canvas to the sample within the pixel
+        let x_offset = (px as f64 + dx) * pixel_size;
+        let y_offset = (py as f64 + dy) * pixel_size;
+
+        // the camera looks toward -z, so +x is to the *left*
+        (half_width - x_offset, half_height - y_offset)
+    }
+
+    pub fn render(&self, world: &World) -> Canvas {
+        self.render_with(world, &RenderOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod camera_tests {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2};
+
+    use apolaki_color::Color;
+    use apolaki_tuple::vector;
+
+    use super::*;
+
+    #[test]
+    fn the_transformation_matrix_for_the_default_orientation() {
+        let from = point(0, 0, 0);
+        let to = point(0, 0, -1);
+        let up = vector(0, 1, 0);
+
+        let t = view_transform(from, to, up);
+
+        assert_eq!(BaseMatrix::identity(), t);
+    }
+
+    #[test]
+    fn a_view_transformation_matrix_looking_in_positive_z_direction() {
+        let from = point(0, 0, 0);
+        let to = point(0, 0, 1);
+        let up = vector(0, 1, 0);
+
+        let t = view_transform(from, to, up);
+
+        assert_eq!(BaseMatrix::identity().scale(-1, 1, -1), t);
+    }
+
+    #[test]
+    fn the_view_transformation_moves_the_world() {
+        let from = point(0, 0, 8);
+        let to = point(0, 0, 0);
+        let up = vector(0, 1, 0);
+
+        let t = view_transform(from, to, up);
+
+        assert_eq!(BaseMatrix::identity().translate(0, 0, -8), t);
+    }
+
+    #[test]
+    fn an_arbitrary_view_transformation() {
+        let from = point(1, 3, 2);
+        let to = point(4, -2, 8);
+        let up = vector(1, 1, 0);
+
+        let t = view_transform(from, to, up);
+
+        let expected = BaseMatrix::from([
+            [-0.50709, 0.50709, 0.67612, -2.36643],
+            [0.76772, 0.60609, 0.12122, -2.82843],
+            [-0.35857, 0.59761, -0.71714, 0.00000],
+            [0.00000, 0.00000, 0.00000, 1.00000],
+        ]);
+        assert_eq!(expected, t);
+    }
+
+    #[test]
+    fn constructing_a_camera() {
+        let c = Camera::new(160, 120, FRAC_PI_2);
+
+        assert_eq!(160, c.hsize);
+        assert_eq!(120, c.vsize);
+        assert_eq!(FRAC_PI_2, c.field_of_view);
+        assert_eq!(BaseMatrix::identity(), c.transform);
+    }
+
+    #[test]
+    fn a_new_camera_is_a_pinhole() {
+        let c = Camera::new(160, 120, FRAC_PI_2);
+
+        assert_eq!(0., c.aperture);
+        assert_eq!(1., c.focal_distance);
+    }
+
+    #[test]
+    fn the_pixel_size_for_a_horizontal_canvas() {
+        let c = Camera::new(200, 125, FRAC_PI_2);
+
+        assert!((c.pixel_size() - 0.01).abs() < 0.00001);
+    }
+
+    #[test]
+    fn the_pixel_size_for_a_vertical_canvas() {
+        let c = Camera::new(125, 200, FRAC_PI_2);
+
+        assert!((c.pixel_size() - 0.01).abs() < 0.00001);
+    }
+
+    #[test]
+    fn constructing_a_ray_through_the_center_of_the_canvas() {
+        let c = Camera::new(201, 101, FRAC_PI_2);
+
+        let r = c.ray_for_pixel(100, 50);
+
+        assert_eq!(point(0, 0, 0), r.origin);
+        assert_eq!(vector(0, 0, -1), r.dir);
+    }
+
+    #[test]
+    fn constructing_a_ray_through_a_corner_of_the_canvas() {
+        let c = Camera::new(201, 101, FRAC_PI_2);
+
+        let r = c.ray_for_pixel(0, 0);
+
+        assert_eq!(point(0, 0, 0), r.origin);
+        assert_eq!(vector(0.66519, 0.33259, -0.66851), r.dir);
+    }
+
+    #[test]
+    fn constructing_a_ray_when_the_camera_is_transformed() {
+        let mut c = Camera::new(201, 101, FRAC_PI_2);
+        c.transform = BaseMatrix::identity()
+            .translate(0, -2, 5)
+            .rotate_y(FRAC_PI_4);
+
+        let r = c.ray_for_pixel(100, 50);
+
+        assert_eq!(point(0, 2, -5), r.origin);
+        assert_eq!(vector(SQRT_2 / 2., 0, -SQRT_2 / 2.), r.dir);
+    }
+
+    #[test]
+    fn a_ray_through_the_middle_of_a_pixel_is_the_pixels_ray() {
+        let c = Camera::new(201, 101, FRAC_PI_2);
+
+        let r = c.ray_for_subpixel(7, 12, 0.5, 0.5);
+
+        assert_eq!(c.ray_for_pixel(7, 12).dir, r.dir);
+    }
+
+    #[test]
+    fn constructing_a_ray_through_the_corner_of_a_pixel() {
+        let c = Camera::new(201, 101, FRAC_PI_2);
+
+        let r = c.ray_for_subpixel(0, 0, 0., 0.);
+
+        assert_eq!(point(0, 0, 0), r.origin);
+        assert_eq!(vector(1, 101. / 201., -1).normalize(), r.dir);
+    }
+
+    #[test]
+    fn a_ray_through_the_center_of_the_lens_is_the_pinhole_ray() {
+        let mut c = Camera::new(201, 101, FRAC_PI_2);
+        c.aperture = 0.5;
+        c.focal_distance = 3.;
+
+        let r = c.ray_through_lens(20, 30, 0.5, 0.5, (0., 0.));
+
+        assert_eq!(c.ray_for_pixel(20, 30).origin, r.origin);
+        assert_eq!(c.ray_for_pixel(20, 30).dir, r.dir);
+    }
+
+    #[test]
+    fn rays_through_the_lens_start_on_the_aperture() {
+        let mut c = Camera::new(201, 101, FRAC_PI_2);
+        c.aperture = 0.5;
+
+        let r = c.ray_through_lens(100, 50, 0.5, 0.5, (1., 0.));
+
+        assert_eq!(point(0.25, 0, 0), r.origin);
+    }
+
+    #[test]
+    fn rays_through_the_lens_meet_on_the_focal_plane() {
+        let mut c = Camera::new(201, 101, FRAC_PI_2);
+        c.transform = view_transform(point(1, 2, -5), point(0, 0, 0), vector(0, 1, 0));
+        c.aperture = 0.5;
+        c.focal_distance = 4.;
+        let pinhole = c.ray_for_pixel(30, 70);
+        let focus = pinhole.position(4. / pinhole.dir.dot(c.transform.invert() * vector(0, 0, -1)));
+
+        for lens in [(1., 0.), (-1., 0.), (0., 1.), (0.3, -0.6)] {
+            let r = c.ray_through_lens(30, 70, 0.5, 0.5, lens);
+            let t = (focus - r.origin).length();
+
+            assert_eq!(focus, r.position(t));
+        }
+    }
+
+    #[test]
+    fn rendering_a_world_with_a_camera() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        let from = point(0, 0, -5);
+        let to = point(0, 0, 0);
+        let up = vector(0, 1, 0);
+        c.transform = view_transform(from, to, up);
+
+        let image = c.render(&w);
+
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), image.pixels[5][5]);
+    }
+}
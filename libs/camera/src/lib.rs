@@ -0,0 +1,5 @@
+mod camera;
+mod render;
+
+pub use camera::*;
+pub use render::*;
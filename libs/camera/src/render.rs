@@ -0,0 +1,446 @@
+use std::f64::consts::PI;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use apolaki_canvas::Canvas;
+use apolaki_color::Color;
+use apolaki_random::Rng;
+use apolaki_world::World;
+
+use crate::camera::Camera;
+
+// where the samples are placed within each of a pixel's N×N cells
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sampling {
+    // the center of each cell
+    #[default]
+    Stratified,
+    // a random point within each cell
+    Jittered,
+}
+
+// how much each sample counts towards its pixel's color
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    #[default]
+    Box,
+    // falls off linearly to zero one pixel away from the center
+    Tent,
+    // a gaussian with a standard deviation of half a pixel
+    Gaussian,
+}
+
+impl Filter {
+    // `dx` and `dy` are the sample's distance from the pixel's center, in pixels
+    pub fn weight(&self, dx: f64, dy: f64) -> f64 {
+        match self {
+            Filter::Box => 1.,
+            Filter::Tent => (1. - dx.abs()).max(0.) * (1. - dy.abs()).max(0.),
+            Filter::Gaussian => {
+                const SIGMA: f64 = 0.5;
+                (-(dx * dx + dy * dy) / (2. * SIGMA * SIGMA)).exp()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderOptions {
+    // number of worker threads; 0 is treated as 1
+    pub threads: usize,
+    // width and height of the square tiles handed out to workers
+    pub tile_size: usize,
+    // each pixel is traced with `samples`×`samples` rays; 0 is treated as 1. A camera with an
+    // aperture needs several samples to blur smoothly.
+    pub samples: usize,
+    pub sampling: Sampling,
+    pub filter: Filter,
+    // seeds jittered sampling, so the same seed renders the same image
+    pub seed: u64,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            tile_size: 16,
+            samples: 1,
+            sampling: Sampling::default(),
+            filter: Filter::default(),
+            seed: 0,
+        }
+    }
+}
+
+// sample positions within a pixel, with (0, 0) at its top-left corner
+fn sample_offsets(samples: usize, sampling: Sampling, rng: &mut Rng) -> Vec<(f64, f64)> {
+    let n = samples.max(1);
+    let cell = 1. / n as f64;
+
+    (0..n)
+        .flat_map(|j| (0..n).map(move |i| (i, j)))
+        .map(|(i, j)| {
+            let (u, v) = match sampling {
+                Sampling::Stratified => (0.5, 0.5),
+                Sampling::Jittered => (rng.next_f64(), rng.next_f64()),
+            };
+            ((i as f64 + u) * cell, (j as f64 + v) * cell)
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Tile {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+// splits an image into row-major tiles, clipping those along the right and bottom edges
+fn tiles(hsize: usize, vsize: usize, tile_size: usize) -> Vec<Tile> {
+    let tile_size = tile_size.max(1);
+
+    (0..vsize)
+        .step_by(tile_size)
+        .flat_map(|y| {
+            (0..hsize).step_by(tile_size).map(move |x| Tile {
+                x,
+                y,
+                width: tile_size.min(hsize - x),
+                height: tile_size.min(vsize - y),
+            })
+        })
+        .collect()
+}
+
+// a uniformly distributed point on the unit disk
+fn sample_disk(rng: &mut Rng) -> (f64, f64) {
+    let r = rng.next_f64().sqrt();
+    let theta = 2. * PI * rng.next_f64();
+    (r * theta.cos(), r * theta.sin())
+}
+
+impl Camera {
+    // Renders tiles across `options.threads` workers. Every pixel is traced independently,
+    // so the image is identical whatever the thread count or tile size.
+    pub fn render_with(&self, world: &World, options: &RenderOptions) -> Canvas {
+        let tiles = tiles(self.hsize, self.vsize, options.tile_size);
+        let next = AtomicUsize::new(0);
+        let threads = options.threads.clamp(1, tiles.len().max(1));
+
+        let rendered: Vec<(Tile, Vec<Color>)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        while let Some(&tile) = tiles.get(next.fetch_add(1, Ordering::Relaxed)) {
+                            done.push((tile, self.render_tile(world, tile, options)));
+                        }
+                        done
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|w| w.join().expect("render thread panicked"))
+                .collect()
+        });
+
+        let mut image = Canvas::with_size(self.hsize, self.vsize);
+        for (tile, colors) in rendered {
+            let coords = (tile.y..tile.y + tile.height)
+                .flat_map(|y| (tile.x..tile.x + tile.width).map(move |x| (x, y)));
+            for ((x, y), color) in coords.zip(colors) {
+                image.write(x, y, color);
+            }
+        }
+        image
+    }
+
+    fn render_tile(&self, world: &World, tile: Tile, options: &RenderOptions) -> Vec<Color> {
+        (tile.y..tile.y + tile.height)
+            .flat_map(|y| (tile.x..tile.x + tile.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.render_pixel(world, x, y, options))
+            .collect()
+    }
+
+    fn render_pixel(&self, world: &World, x: usize, y: usize, options: &RenderOptions) -> Color {
+        // seeded per pixel, so the samples don't depend on which thread traces them
+        let mut rng = Rng::for_stream(options.seed, (y * self.hsize + x) as u64);
+
+        let (sum, total_weight) = sample_offsets(options.samples, options.sampling, &mut rng)
+            .into_iter()
+            .map(|(dx, dy)| {
+                let weight = options.filter.weight(dx - 0.5, dy - 0.5);
+                // with an aperture, every sample also passes through a different part of the lens
+                let ray = if self.aperture > 0. {
+                    self.ray_through_lens(x, y, dx, dy, sample_disk(&mut rng))
+                } else {
+                    self.ray_for_subpixel(x, y, dx, dy)
+                };
+                let color = world.color_at(ray);
+                (color * weight, weight)
+            })
+            .fold((Color::BLACK, 0.), |(sum, total), (color, weight)| {
+                (sum + color, total + weight)
+            });
+
+        sum * (1. / total_weight)
+    }
+}
+
+#[cfg(test)]
+mod render_tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use apolaki_light::Light;
+    use apolaki_material::Material;
+    use apolaki_matrix::BaseMatrix;
+    use apolaki_objects::Sphere;
+    use apolaki_tuple::{point, vector};
+
+    use super::*;
+    use crate::view_transform;
+
+    fn bits(c: &Color) -> [u64; 3] {
+        [c.r().to_bits(), c.g().to_bits(), c.b().to_bits()]
+    }
+
+    // a single white, unshaded sphere whose edge runs just right of the view's center
+    fn half_covered_world() -> World {
+        let mut w = World::new();
+        w.add_light(Light::new(point(0, 0, 10), Color::WHITE));
+        w.add_object(Sphere {
+            transform: BaseMatrix::identity()
+                .scale(2017.4, 2017.4, 2017.4)
+                .translate(-2000, 0, -2000),
+            material: Material {
+                ambient: 1.,
+                diffuse: 0.,
+                specular: 0.,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        w
+    }
+
+    fn test_camera() -> Camera {
+        let mut c = Camera::new(23, 17, FRAC_PI_2);
+        c.transform = view_transform(point(0, 0, -5), point(0, 0, 0), vector(0, 1, 0));
+        c
+    }
+
+    #[test]
+    fn tiles_cover_every_pixel_exactly_once() {
+        let tiles = tiles(23, 17, 8);
+        let mut seen = vec![vec![0; 23]; 17];
+
+        for t in &tiles {
+            for row in seen.iter_mut().skip(t.y).take(t.height) {
+                for pixel in row.iter_mut().skip(t.x).take(t.width) {
+                    *pixel += 1;
+                }
+            }
+        }
+
+        assert_eq!(9, tiles.len());
+        assert!(seen.iter().flatten().all(|&n| n == 1));
+    }
+
+    #[test]
+    fn a_zero_tile_size_falls_back_to_single_pixels() {
+        assert_eq!(6, tiles(3, 2, 0).len());
+    }
+
+    #[test]
+    fn rendering_is_identical_regardless_of_thread_count() {
+        let w = World::default();
+        let c = test_camera();
+
+        let single = c.render_with(
+            &w,
+            &RenderOptions {
+                threads: 1,
+                tile_size: 4,
+                ..Default::default()
+            },
+        );
+        let parallel = c.render_with(
+            &w,
+            &RenderOptions {
+                threads: 4,
+                tile_size: 5,
+                ..Default::default()
+            },
+        );
+
+        // bit for bit, not merely within Color's epsilon
+        for (a, b) in single
+            .pixels
+            .iter()
+            .flatten()
+            .zip(parallel.pixels.iter().flatten())
+        {
+            assert_eq!(bits(a), bits(b));
+        }
+    }
+
+    #[test]
+    fn rendering_matches_tracing_each_pixel_in_turn() {
+        let w = World::default();
+        let c = test_camera();
+
+        let image = c.render(&w);
+
+        for y in 0..c.vsize {
+            for x in 0..c.hsize {
+                assert_eq!(w.color_at(c.ray_for_pixel(x, y)), image.pixels[y][x]);
+            }
+        }
+    }
+
+    #[test]
+    fn stratified_samples_sit_in_the_middle_of_each_cell() {
+        let offsets = sample_offsets(2, Sampling::Stratified, &mut Rng::new(0));
+
+        assert_eq!(
+            vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)],
+            offsets
+        );
+    }
+
+    #[test]
+    fn jittered_samples_stay_within_their_cells() {
+        let offsets = sample_offsets(3, Sampling::Jittered, &mut Rng::new(9));
+        let cell = 1. / 3.;
+
+        for (index, (dx, dy)) in offsets.into_iter().enumerate() {
+            let (i, j) = ((index % 3) as f64, (index / 3) as f64);
+            assert!((i * cell..(i + 1.) * cell).contains(&dx));
+            assert!((j * cell..(j + 1.) * cell).contains(&dy));
+        }
+    }
+
+    #[test]
+    fn filters_weigh_the_center_of_a_pixel_the_most() {
+        for filter in [Filter::Box, Filter::Tent, Filter::Gaussian] {
+            assert_eq!(1., filter.weight(0., 0.));
+            assert!(filter.weight(0.5, 0.5) <= filter.weight(0.25, 0.));
+        }
+        assert_eq!(0.25, Filter::Tent.weight(0.5, 0.5));
+    }
+
+    #[test]
+    fn a_single_sample_per_pixel_traces_the_pixels_center() {
+        let w = World::default();
+        let c = test_camera();
+        let options = RenderOptions {
+            samples: 1,
+            sampling: Sampling::Stratified,
+            filter: Filter::Gaussian,
+            ..Default::default()
+        };
+
+        let image = c.render_with(&w, &options);
+
+        assert_eq!(
+            bits(&w.color_at(c.ray_for_pixel(11, 8))),
+            bits(&image.pixels[8][11])
+        );
+    }
+
+    #[test]
+    fn supersampling_averages_across_an_edge() {
+        let w = half_covered_world();
+        let c = Camera::new(1, 1, FRAC_PI_2);
+
+        let aliased = c.render(&w);
+        let smoothed = c.render_with(
+            &w,
+            &RenderOptions {
+                samples: 2,
+                ..Default::default()
+            },
+        );
+
+        // the single ray through the center just catches the sphere
+        assert_eq!(Color::WHITE, aliased.pixels[0][0]);
+        // half the samples hit the sphere, and half miss it
+        assert_eq!(Color::new(0.5, 0.5, 0.5), smoothed.pixels[0][0]);
+    }
+
+    #[test]
+    fn jittered_renders_are_repeatable_for_a_seed() {
+        let w = World::default();
+        let c = test_camera();
+        let options = |seed| RenderOptions {
+            samples: 2,
+            sampling: Sampling::Jittered,
+            filter: Filter::Tent,
+            seed,
+            ..Default::default()
+        };
+
+        let a = c.render_with(&w, &options(3));
+        let b = c.render_with(
+            &w,
+            &RenderOptions {
+                threads: 3,
+                ..options(3)
+            },
+        );
+        let other = c.render_with(&w, &options(4));
+
+        let a_bits: Vec<_> = a.pixels.iter().flatten().map(bits).collect();
+        let b_bits: Vec<_> = b.pixels.iter().flatten().map(bits).collect();
+        let other_bits: Vec<_> = other.pixels.iter().flatten().map(bits).collect();
+        assert_eq!(a_bits, b_bits);
+        assert_ne!(a_bits, other_bits);
+    }
+
+    #[test]
+    fn lens_samples_lie_on_the_unit_disk() {
+        let mut rng = Rng::new(5);
+
+        for _ in 0..100 {
+            let (x, y) = sample_disk(&mut rng);
+            assert!(x * x + y * y <= 1.);
+        }
+    }
+
+    #[test]
+    fn objects_off_the_focal_plane_are_blurred() {
+        let mut w = World::new();
+        w.add_light(Light::new(point(0, 0, 10), Color::WHITE));
+        w.add_object(Sphere {
+            transform: BaseMatrix::identity().translate(0, 0, -10),
+            material: Material {
+                ambient: 1.,
+                diffuse: 0.,
+                specular: 0.,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        // a narrow view that only ever sees the sphere through a pinhole
+        let mut c = Camera::new(1, 1, 0.1);
+        c.aperture = 8.;
+        let options = RenderOptions {
+            samples: 4,
+            sampling: Sampling::Jittered,
+            ..Default::default()
+        };
+
+        c.focal_distance = 9.;
+        let in_focus = c.render_with(&w, &options).pixels[0][0];
+        c.focal_distance = 100.;
+        let out_of_focus = c.render_with(&w, &options).pixels[0][0];
+
+        assert_eq!(Color::WHITE, in_focus);
+        assert!(out_of_focus.r() < 1.);
+    }
+}
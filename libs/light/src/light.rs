@@ -1,9 +1,100 @@
 use apolaki_color::Color;
+use apolaki_random::Rng;
 use apolaki_tuple::*;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+    // all light leaves from `Light::position`
+    Point,
+    // A rectangle with one corner at `corner`, split into `usteps`×`vsteps` cells spanned
+    // by `uvec` and `vvec`. Shading samples one point per cell, jittered within the cell
+    // when `jitter` holds a seed.
+    Area {
+        corner: Tuple,
+        uvec: Tuple,
+        usteps: usize,
+        vvec: Tuple,
+        vsteps: usize,
+        jitter: Option<u64>,
+    },
+    // parallel rays travelling along `direction`, as from a very distant sun
+    Directional {
+        direction: Tuple,
+    },
+    // a point light restricted to a cone around `direction`, fading out between the
+    // `inner_angle` and `outer_angle` (in radians, measured from `direction`)
+    Spot {
+        direction: Tuple,
+        inner_angle: f64,
+        outer_angle: f64,
+    },
+}
+
+// how a light weakens with distance; directional lights never do
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Falloff {
+    // full strength at any distance
+    #[default]
+    None,
+    // 1 / (constant + linear·d + quadratic·d²), but never brighter than full strength, so
+    // zero or negative coefficients can't make it infinite or negative
+    Polynomial {
+        constant: f64,
+        linear: f64,
+        quadratic: f64,
+    },
+    // 1 / (d² + 1): inverse-square away from the light but full strength right next to it,
+    // and windowed to reach zero at `range`
+    InverseSquare {
+        range: f64,
+    },
+}
+
+impl Falloff {
+    pub fn inverse_square(range: f64) -> Self {
+        if range.is_nan() || range <= 0. {
+            panic!("parameter `range` must be positive")
+        }
+        Falloff::InverseSquare { range }
+    }
+
+    pub fn attenuation(&self, distance: f64) -> f64 {
+        match *self {
+            Falloff::None => 1.,
+            Falloff::Polynomial {
+                constant,
+                linear,
+                quadratic,
+            } => 1. / (constant + linear * distance + quadratic * distance * distance).max(1.),
+            // without a range the light reaches nothing
+            Falloff::InverseSquare { range } if range.is_nan() || range <= 0. => 0.,
+            Falloff::InverseSquare { range } => {
+                let window = (1. - (distance / range).powi(4)).clamp(0., 1.).powi(2);
+                // the + 1 keeps the light finite right next to it
+                window / (distance * distance + 1.)
+            }
+        }
+    }
+}
+
+// how light from one sample on a light arrives at a point
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Incidence {
+    // normalized, pointing from the point towards the light
+    pub light_vector: Tuple,
+    // how far the light is; infinite for directional lights
+    pub distance: f64,
+    // how much of the light's intensity is left on arrival, from 0 to 1
+    pub attenuation: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Light {
+    // unused by directional lights, which have no position
     pub position: Tuple,
     pub intensity: Color,
+    pub kind: LightKind,
+    pub falloff: Falloff,
 }
 
 impl Light {
@@ -11,12 +102,187 @@ impl Light {
         Self {
             position,
             intensity,
+            kind: LightKind::Point,
+            falloff: Falloff::None,
+        }
+    }
+
+    // a rectangular light spanning `full_uvec` and `full_vvec` from `corner`
+    pub fn area(
+        corner: Tuple,
+        full_uvec: Tuple,
+        usteps: usize,
+        full_vvec: Tuple,
+        vsteps: usize,
+        intensity: Color,
+    ) -> Self {
+        let usteps = usteps.max(1);
+        let vsteps = vsteps.max(1);
+
+        Self {
+            position: corner + full_uvec / 2 + full_vvec / 2,
+            intensity,
+            kind: LightKind::Area {
+                corner,
+                uvec: full_uvec / usteps as f64,
+                usteps,
+                vvec: full_vvec / vsteps as f64,
+                vsteps,
+                jitter: None,
+            },
+            falloff: Falloff::None,
+        }
+    }
+
+    // a light shining along `direction` everywhere, with no position and no falloff
+    pub fn directional(direction: Tuple, intensity: Color) -> Self {
+        Self {
+            position: point(0, 0, 0),
+            intensity,
+            kind: LightKind::Directional {
+                direction: direction.normalize(),
+            },
+            falloff: Falloff::None,
+        }
+    }
+
+    pub fn spot(
+        position: Tuple,
+        direction: Tuple,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Color,
+    ) -> Self {
+        Self {
+            position,
+            intensity,
+            kind: LightKind::Spot {
+                direction: direction.normalize(),
+                inner_angle,
+                outer_angle,
+            },
+            falloff: Falloff::None,
+        }
+    }
+
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    // jitters an area light's samples, repeatably for a given seed
+    pub fn jittered(mut self, seed: u64) -> Self {
+        if let LightKind::Area { jitter, .. } = &mut self.kind {
+            *jitter = Some(seed);
+        }
+        self
+    }
+
+    // how many points `sample_points` returns
+    pub fn samples(&self) -> usize {
+        match self.kind {
+            LightKind::Area { usteps, vsteps, .. } => usteps * vsteps,
+            _ => 1,
+        }
+    }
+
+    // the point within cell `u`, `v` of an area light, offset by `jitter` in [0, 1)
+    fn point_on_light(&self, u: usize, v: usize, jitter: (f64, f64)) -> Tuple {
+        match self.kind {
+            LightKind::Area {
+                corner, uvec, vvec, ..
+            } => corner + uvec * (u as f64 + jitter.0) + vvec * (v as f64 + jitter.1),
+            _ => self.position,
+        }
+    }
+
+    // The points on the light that shading `at` samples. Jitter is seeded by `at`, so the
+    // same point always sees the same samples, whichever thread shades it.
+    pub fn sample_points(&self, at: Tuple) -> Vec<Tuple> {
+        match self.kind {
+            LightKind::Area {
+                usteps,
+                vsteps,
+                jitter,
+                ..
+            } => {
+                let mut rng = jitter.map(|seed| {
+                    let key = at.x().to_bits()
+                        ^ at.y().to_bits().rotate_left(21)
+                        ^ at.z().to_bits().rotate_left(42);
+                    Rng::for_stream(seed, key)
+                });
+
+                (0..vsteps)
+                    .flat_map(|v| (0..usteps).map(move |u| (u, v)))
+                    .map(|(u, v)| {
+                        let offset = match rng.as_mut() {
+                            Some(rng) => (rng.next_f64(), rng.next_f64()),
+                            None => (0.5, 0.5),
+                        };
+                        self.point_on_light(u, v, offset)
+                    })
+                    .collect()
+            }
+            _ => vec![self.position],
+        }
+    }
+
+    // how light from each of the light's samples arrives at `at`
+    pub fn incidences(&self, at: Tuple) -> Vec<Incidence> {
+        if let LightKind::Directional { direction } = self.kind {
+            return vec![Incidence {
+                light_vector: -direction,
+                distance: f64::INFINITY,
+                attenuation: 1.,
+            }];
+        }
+
+        self.sample_points(at)
+            .into_iter()
+            .map(|sample| {
+                let v = sample - at;
+                let light_vector = v.normalize();
+                let distance = v.length();
+                Incidence {
+                    light_vector,
+                    distance,
+                    attenuation: self.cone_attenuation(light_vector)
+                        * self.falloff.attenuation(distance),
+                }
+            })
+            .collect()
+    }
+
+    // a spot light's smooth falloff between its inner and outer cones
+    fn cone_attenuation(&self, light_vector: Tuple) -> f64 {
+        let LightKind::Spot {
+            direction,
+            inner_angle,
+            outer_angle,
+        } = self.kind
+        else {
+            return 1.;
+        };
+
+        let cos_angle = (-light_vector).dot(direction);
+        let (cos_inner, cos_outer) = (inner_angle.cos(), outer_angle.cos());
+
+        if cos_angle >= cos_inner {
+            1.
+        } else if cos_angle <= cos_outer {
+            0.
+        } else {
+            let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3. - 2. * t)
         }
     }
 }
 
 #[cfg(test)]
 mod light_tests {
+    use std::f64::consts::PI;
+
     use super::*;
 
     #[test]
@@ -28,5 +294,257 @@ mod light_tests {
 
         assert_eq!(position, light.position);
         assert_eq!(intensity, light.intensity);
+        assert_eq!(vec![position], light.sample_points(point(1, 2, 3)));
+    }
+
+    #[test]
+    fn creating_an_area_light() {
+        let corner = point(0, 0, 0);
+        let v1 = vector(2, 0, 0);
+        let v2 = vector(0, 0, 1);
+
+        let light = Light::area(corner, v1, 4, v2, 2, Color::WHITE);
+
+        assert_eq!(point(1, 0, 0.5), light.position);
+        assert_eq!(8, light.samples());
+        assert_eq!(
+            LightKind::Area {
+                corner,
+                uvec: vector(0.5, 0, 0),
+                usteps: 4,
+                vvec: vector(0, 0, 0.5),
+                vsteps: 2,
+                jitter: None,
+            },
+            light.kind
+        );
+    }
+
+    #[test]
+    fn finding_a_single_point_on_an_area_light() {
+        let light = Light::area(
+            point(0, 0, 0),
+            vector(2, 0, 0),
+            4,
+            vector(0, 0, 1),
+            2,
+            Color::WHITE,
+        );
+        let cases = [
+            ((0, 0), point(0.25, 0, 0.25)),
+            ((1, 0), point(0.75, 0, 0.25)),
+            ((0, 1), point(0.25, 0, 0.75)),
+            ((2, 0), point(1.25, 0, 0.25)),
+            ((3, 1), point(1.75, 0, 0.75)),
+        ];
+
+        for ((u, v), expected) in cases {
+            assert_eq!(expected, light.point_on_light(u, v, (0.5, 0.5)));
+        }
+    }
+
+    #[test]
+    fn an_area_light_samples_the_center_of_every_cell() {
+        let light = Light::area(
+            point(0, 0, 0),
+            vector(2, 0, 0),
+            2,
+            vector(0, 0, 2),
+            2,
+            Color::WHITE,
+        );
+
+        let samples = light.sample_points(point(0, 5, 0));
+
+        assert_eq!(
+            vec![
+                point(0.5, 0, 0.5),
+                point(1.5, 0, 0.5),
+                point(0.5, 0, 1.5),
+                point(1.5, 0, 1.5),
+            ],
+            samples
+        );
+    }
+
+    #[test]
+    fn jittered_samples_stay_within_their_cells_and_repeat_per_point() {
+        let light = Light::area(
+            point(0, 0, 0),
+            vector(2, 0, 0),
+            2,
+            vector(0, 0, 2),
+            2,
+            Color::WHITE,
+        )
+        .jittered(11);
+
+        let samples = light.sample_points(point(0, 5, 0));
+
+        for (index, sample) in samples.iter().enumerate() {
+            let (u, v) = ((index % 2) as f64, (index / 2) as f64);
+            assert!((u..u + 1.).contains(&sample.x()));
+            assert!((v..v + 1.).contains(&sample.z()));
+        }
+        assert_eq!(samples, light.sample_points(point(0, 5, 0)));
+        assert_ne!(samples, light.sample_points(point(0, 5, 1)));
+    }
+
+    #[test]
+    fn a_point_light_shines_from_its_position() {
+        let light = Light::new(point(0, 10, 0), Color::WHITE);
+
+        let incidences = light.incidences(point(0, 0, 0));
+
+        assert_eq!(
+            vec![Incidence {
+                light_vector: vector(0, 1, 0),
+                distance: 10.,
+                attenuation: 1.,
+            }],
+            incidences
+        );
+    }
+
+    #[test]
+    fn a_directional_light_shines_the_same_way_everywhere() {
+        let light = Light::directional(vector(0, -2, 0), Color::WHITE);
+
+        for p in [point(0, 0, 0), point(100, -50, 3)] {
+            let incidences = light.incidences(p);
+
+            assert_eq!(1, incidences.len());
+            assert_eq!(vector(0, 1, 0), incidences[0].light_vector);
+            assert_eq!(f64::INFINITY, incidences[0].distance);
+            assert_eq!(1., incidences[0].attenuation);
+        }
+    }
+
+    #[test]
+    fn a_spot_light_is_full_strength_inside_its_inner_cone() {
+        let light = Light::spot(
+            point(0, 0, 0),
+            vector(0, -1, 0),
+            PI / 6.,
+            PI / 3.,
+            Color::WHITE,
+        );
+
+        let incidences = light.incidences(point(0.1, -1, 0));
+
+        assert_eq!(1., incidences[0].attenuation);
+    }
+
+    #[test]
+    fn a_spot_light_is_dark_outside_its_outer_cone() {
+        let light = Light::spot(
+            point(0, 0, 0),
+            vector(0, -1, 0),
+            PI / 6.,
+            PI / 3.,
+            Color::WHITE,
+        );
+
+        assert_eq!(0., light.incidences(point(2, -1, 0))[0].attenuation);
+        assert_eq!(0., light.incidences(point(0, 1, 0))[0].attenuation);
+    }
+
+    #[test]
+    fn a_spot_light_fades_out_between_its_cones() {
+        let light = Light::spot(
+            point(0, 0, 0),
+            vector(0, -1, 0),
+            PI / 6.,
+            PI / 3.,
+            Color::WHITE,
+        );
+        // halfway between the cosines of the inner and outer angles
+        let cos = ((PI / 6.).cos() + (PI / 3.).cos()) / 2.;
+        let x = (1. / (cos * cos) - 1.).sqrt();
+
+        let attenuation = light.incidences(point(x, -1, 0))[0].attenuation;
+
+        assert!((attenuation - 0.5).abs() < 0.00001);
+    }
+
+    #[test]
+    fn lights_do_not_fall_off_by_default() {
+        let light = Light::new(point(0, 100, 0), Color::WHITE);
+
+        assert_eq!(Falloff::None, light.falloff);
+        assert_eq!(1., light.incidences(point(0, 0, 0))[0].attenuation);
+    }
+
+    #[test]
+    fn polynomial_falloff() {
+        let falloff = Falloff::Polynomial {
+            constant: 1.,
+            linear: 0.5,
+            quadratic: 0.25,
+        };
+
+        assert_eq!(1., falloff.attenuation(0.));
+        assert_eq!(1. / 3., falloff.attenuation(2.));
+    }
+
+    #[test]
+    fn polynomial_falloff_never_brightens_a_light() {
+        let zero = Falloff::Polynomial {
+            constant: 0.,
+            linear: 0.,
+            quadratic: 0.,
+        };
+        let negative = Falloff::Polynomial {
+            constant: -1.,
+            linear: 0.,
+            quadratic: -0.5,
+        };
+
+        assert_eq!(1., zero.attenuation(0.));
+        assert_eq!(1., zero.attenuation(5.));
+        assert_eq!(1., negative.attenuation(2.));
+    }
+
+    #[test]
+    #[should_panic(expected = "`range` must be positive")]
+    fn inverse_square_falloff_needs_a_positive_range() {
+        Falloff::inverse_square(0.);
+    }
+
+    #[test]
+    fn inverse_square_falloff_without_a_range_reaches_nothing() {
+        let falloff = Falloff::InverseSquare { range: 0. };
+
+        assert_eq!(0., falloff.attenuation(0.));
+        assert_eq!(0., falloff.attenuation(1.));
+    }
+
+    #[test]
+    fn inverse_square_falloff_reaches_zero_at_its_range() {
+        let falloff = Falloff::inverse_square(10.);
+
+        assert_eq!(1., falloff.attenuation(0.));
+        assert!(falloff.attenuation(2.) < falloff.attenuation(1.));
+        assert_eq!(0., falloff.attenuation(10.));
+        assert_eq!(0., falloff.attenuation(20.));
+    }
+
+    #[test]
+    fn falloff_attenuates_a_lights_incidences() {
+        let light = Light::new(point(0, 2, 0), Color::WHITE).with_falloff(Falloff::Polynomial {
+            constant: 1.,
+            linear: 0.5,
+            quadratic: 0.25,
+        });
+
+        assert_eq!(1. / 3., light.incidences(point(0, 0, 0))[0].attenuation);
+    }
+
+    #[test]
+    fn directional_lights_ignore_falloff() {
+        let light = Light::directional(vector(0, -1, 0), Color::WHITE)
+            .with_falloff(Falloff::inverse_square(1.));
+
+        assert_eq!(1., light.incidences(point(0, -50, 0))[0].attenuation);
     }
 }
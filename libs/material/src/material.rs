@@ -1,51 +1,105 @@
+use std::sync::Arc;
+
 use apolaki_color::Color;
 use apolaki_light::Light;
+use apolaki_pattern::Pattern;
 use apolaki_tuple::Tuple;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct Material {
     pub color: Color,
+    // colors the surface in place of `color` when set
+    pub pattern: Option<Arc<dyn Pattern>>,
     pub ambient: f64,
     pub diffuse: f64,
     pub specular: f64,
     pub shininess: f64,
+    pub reflective: f64,
+    pub transparency: f64,
+    pub refractive_index: f64,
 }
 
 impl Material {
+    // the surface color at a point in the object's space
+    pub fn color_at(&self, object_point: Tuple) -> Color {
+        match &self.pattern {
+            Some(pattern) => pattern.color_at(object_point),
+            None => self.color,
+        }
+    }
+
+    // `object_point` is `point` in the lit object's space, where its pattern is sampled.
+    // `intensity` is the fraction of the light reaching `point`: 0 in full shadow, 1 unoccluded
     pub fn lighting(
         &self,
         light: &Light,
         point: Tuple,
+        object_point: Tuple,
         eye_vector: Tuple,
         normal_vector: Tuple,
+        intensity: f64,
     ) -> Color {
-        let effective_color = self.color * light.intensity;
-
-        let light_vector = (light.position - point).normalize();
+        let effective_color = self.color_at(object_point) * light.intensity;
 
         let ambient = effective_color * self.ambient;
 
-        let light_dot_normal = light_vector.dot(normal_vector);
-
-        if light_dot_normal < 0. {
-            // light is on the other side of the surface
-            ambient + (Color::BLACK * 2)
-        } else {
-            let diffuse = effective_color * self.diffuse * light_dot_normal;
-            let specular = {
-                let reflect_vector = -light_vector.reflect(normal_vector);
-                let reflect_dot_eye = reflect_vector.dot(eye_vector);
+        if intensity == 0. {
+            // only ambient light reaches a point in shadow
+            return ambient;
+        }
 
-                if reflect_dot_eye <= 0. {
-                    Color::BLACK
-                } else {
-                    let factor = reflect_dot_eye.powf(self.shininess);
+        // diffuse and specular are averaged over every sample on the light
+        let incidences = light.incidences(point);
+        let sum = incidences
+            .iter()
+            .map(|incidence| {
+                let light_vector = incidence.light_vector;
+                let light_dot_normal = light_vector.dot(normal_vector);
 
-                    light.intensity * self.specular * factor
+                if light_dot_normal < 0. || incidence.attenuation == 0. {
+                    // light is on the other side of the surface, or doesn't reach it
+                    return Color::BLACK;
                 }
-            };
-            ambient + diffuse + specular
-        }
+
+                let diffuse = effective_color * self.diffuse * light_dot_normal;
+                let specular = {
+                    let reflect_vector = -light_vector.reflect(normal_vector);
+                    let reflect_dot_eye = reflect_vector.dot(eye_vector);
+
+                    if reflect_dot_eye <= 0. {
+                        Color::BLACK
+                    } else {
+                        let factor = reflect_dot_eye.powf(self.shininess);
+
+                        light.intensity * self.specular * factor
+                    }
+                };
+                (diffuse + specular) * incidence.attenuation
+            })
+            .fold(Color::BLACK, |acc, c| acc + c);
+
+        ambient + sum * (intensity / incidences.len() as f64)
+    }
+}
+
+// patterns are compared by identity, not by value
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        let same_pattern = match (&self.pattern, &other.pattern) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+
+        same_pattern
+            && self.color == other.color
+            && self.ambient == other.ambient
+            && self.diffuse == other.diffuse
+            && self.specular == other.specular
+            && self.shininess == other.shininess
+            && self.reflective == other.reflective
+            && self.transparency == other.transparency
+            && self.refractive_index == other.refractive_index
     }
 }
 
@@ -53,20 +107,25 @@ impl Default for Material {
     fn default() -> Self {
         Self {
             color: (1, 1, 1).into(),
+            pattern: None,
             ambient: 0.1,
             diffuse: 0.9,
             specular: 0.9,
             shininess: 200.0,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
         }
     }
 }
 
 #[cfg(test)]
 mod material_tests {
-    use std::f64::consts::SQRT_2;
+    use std::f64::consts::{PI, SQRT_2};
 
     use apolaki_color::Color;
-    use apolaki_light::Light;
+    use apolaki_light::{Falloff, Light};
+    use apolaki_pattern::Stripe;
     use apolaki_tuple::*;
 
     use super::*;
@@ -90,13 +149,29 @@ mod material_tests {
         assert_eq!(200.0, m.shininess);
     }
 
+    #[test]
+    fn reflectivity_for_the_default_material() {
+        let m = Material::default();
+
+        assert_eq!(0.0, m.reflective);
+    }
+
+    #[test]
+    fn transparency_and_refractive_index_for_the_default_material() {
+        let m = Material::default();
+
+        assert_eq!(0.0, m.transparency);
+        assert_eq!(1.0, m.refractive_index);
+    }
+
     #[test]
     fn lighting_with_eye_between_light_and_surface() {
         let eyev = vector(0, 0, -1);
         let normalv = vector(0, 0, -1);
         let light = Light::new(point(0, 0, -10), (1, 1, 1).into());
 
-        let result = test_material().lighting(&light, test_position(), eyev, normalv);
+        let result =
+            test_material().lighting(&light, test_position(), test_position(), eyev, normalv, 1.0);
 
         assert_eq!(Color::new(1.9, 1.9, 1.9), result)
     }
@@ -107,7 +182,8 @@ mod material_tests {
         let normalv = vector(0, 0, -1);
         let light = Light::new(point(0, 0, -10), (1, 1, 1).into());
 
-        let result = test_material().lighting(&light, test_position(), eyev, normalv);
+        let result =
+            test_material().lighting(&light, test_position(), test_position(), eyev, normalv, 1.0);
 
         assert_eq!(Color::new(1., 1., 1.), result)
     }
@@ -118,7 +194,8 @@ mod material_tests {
         let normalv = vector(0, 0, -1);
         let light = Light::new(point(0, 10, -10), (1, 1, 1).into());
 
-        let result = test_material().lighting(&light, test_position(), eyev, normalv);
+        let result =
+            test_material().lighting(&light, test_position(), test_position(), eyev, normalv, 1.0);
 
         assert_eq!(Color::new(0.7364, 0.7364, 0.7364), result)
     }
@@ -129,7 +206,8 @@ mod material_tests {
         let normalv = vector(0, 0, -1);
         let light = Light::new(point(0, 10, -10), (1, 1, 1).into());
 
-        let result = test_material().lighting(&light, test_position(), eyev, normalv);
+        let result =
+            test_material().lighting(&light, test_position(), test_position(), eyev, normalv, 1.0);
 
         assert_eq!(Color::new(1.6364, 1.6364, 1.6364), result)
     }
@@ -140,8 +218,238 @@ mod material_tests {
         let normalv = vector(0, 0, -1);
         let light = Light::new(point(0, 0, 10), (1, 1, 1).into());
 
-        let result = test_material().lighting(&light, test_position(), eyev, normalv);
+        let result =
+            test_material().lighting(&light, test_position(), test_position(), eyev, normalv, 1.0);
+
+        assert_eq!(Color::new(0.1, 0.1, 0.1), result)
+    }
+
+    #[test]
+    fn lighting_with_the_surface_in_shadow() {
+        let eyev = vector(0, 0, -1);
+        let normalv = vector(0, 0, -1);
+        let light = Light::new(point(0, 0, -10), (1, 1, 1).into());
+        let in_shadow = 0.0;
+
+        let result = test_material().lighting(
+            &light,
+            test_position(),
+            test_position(),
+            eyev,
+            normalv,
+            in_shadow,
+        );
+
+        assert_eq!(Color::new(0.1, 0.1, 0.1), result)
+    }
+
+    #[test]
+    fn lighting_uses_light_intensity_to_attenuate_color() {
+        let m = Material {
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.,
+            color: Color::new(1, 1, 1),
+            ..Default::default()
+        };
+        let light = Light::new(point(0, 0, -10), (1, 1, 1).into());
+        let p = point(0, 0, -1);
+        let eyev = vector(0, 0, -1);
+        let normalv = vector(0, 0, -1);
+
+        for (intensity, expected) in [(1.0, 1.0), (0.5, 0.55), (0.0, 0.1)] {
+            let result = m.lighting(&light, p, p, eyev, normalv, intensity);
+
+            assert_eq!(Color::new(expected, expected, expected), result);
+        }
+    }
+
+    #[test]
+    fn lighting_samples_the_area_light() {
+        let light = Light::area(
+            point(-0.5, -0.5, -5),
+            vector(1, 0, 0),
+            2,
+            vector(0, 1, 0),
+            2,
+            (1, 1, 1).into(),
+        );
+        let m = Material {
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.,
+            color: Color::new(1, 1, 1),
+            ..Default::default()
+        };
+        let eye = point(0, 0, -5);
+        let cases = [
+            (point(0, 0, -1), Color::new(0.9965, 0.9965, 0.9965)),
+            (
+                point(0, SQRT_2 / 2., -SQRT_2 / 2.),
+                Color::new(0.62318, 0.62318, 0.62318),
+            ),
+        ];
+
+        for (pt, expected) in cases {
+            let eyev = (eye - pt).normalize();
+            // a unit sphere at the origin
+            let normalv = vector(pt.x(), pt.y(), pt.z());
+
+            let result = m.lighting(&light, pt, pt, eyev, normalv, 1.0);
+
+            assert_eq!(expected, result);
+        }
+    }
+
+    #[test]
+    fn lighting_with_a_directional_light_from_above() {
+        let eyev = vector(0, 1, 0);
+        let normalv = vector(0, 1, 0);
+        let light = Light::directional(vector(0, -1, 0), (1, 1, 1).into());
+
+        let result = test_material().lighting(
+            &light,
+            point(50, 0, -20),
+            point(50, 0, -20),
+            eyev,
+            normalv,
+            1.0,
+        );
+
+        assert_eq!(Color::new(1.9, 1.9, 1.9), result)
+    }
+
+    #[test]
+    fn lighting_outside_a_spot_lights_cone() {
+        let eyev = vector(0, 0, -1);
+        let normalv = vector(0, 0, -1);
+        let light = Light::spot(
+            point(0, 0, -10),
+            vector(0, 1, 0),
+            PI / 8.,
+            PI / 6.,
+            (1, 1, 1).into(),
+        );
+
+        let result =
+            test_material().lighting(&light, test_position(), test_position(), eyev, normalv, 1.0);
 
         assert_eq!(Color::new(0.1, 0.1, 0.1), result)
     }
+
+    #[test]
+    fn lighting_inside_a_spot_lights_cone() {
+        let eyev = vector(0, 0, -1);
+        let normalv = vector(0, 0, -1);
+        let light = Light::spot(
+            point(0, 0, -10),
+            vector(0, 0, 1),
+            PI / 8.,
+            PI / 6.,
+            (1, 1, 1).into(),
+        );
+
+        let result =
+            test_material().lighting(&light, test_position(), test_position(), eyev, normalv, 1.0);
+
+        assert_eq!(Color::new(1.9, 1.9, 1.9), result)
+    }
+
+    #[test]
+    fn lighting_with_a_falling_off_light() {
+        let eyev = vector(0, 0, -1);
+        let normalv = vector(0, 0, -1);
+        let light =
+            Light::new(point(0, 0, -10), (1, 1, 1).into()).with_falloff(Falloff::Polynomial {
+                constant: 2.,
+                linear: 0.,
+                quadratic: 0.,
+            });
+
+        let result =
+            test_material().lighting(&light, test_position(), test_position(), eyev, normalv, 1.0);
+
+        // ambient is untouched; diffuse and specular are halved
+        assert_eq!(Color::new(1., 1., 1.), result)
+    }
+
+    #[test]
+    fn the_default_material_has_no_pattern() {
+        assert!(Material::default().pattern.is_none());
+    }
+
+    #[test]
+    fn lighting_with_a_pattern_applied() {
+        let m = Material {
+            pattern: Some(Arc::new(Stripe::new(Color::WHITE, Color::BLACK))),
+            ambient: 1.,
+            diffuse: 0.,
+            specular: 0.,
+            ..Default::default()
+        };
+        let eyev = vector(0, 0, -1);
+        let normalv = vector(0, 0, -1);
+        let light = Light::new(point(0, 0, -10), Color::WHITE);
+
+        let c1 = m.lighting(
+            &light,
+            point(0.9, 0, 0),
+            point(0.9, 0, 0),
+            eyev,
+            normalv,
+            1.0,
+        );
+        let c2 = m.lighting(
+            &light,
+            point(1.1, 0, 0),
+            point(1.1, 0, 0),
+            eyev,
+            normalv,
+            1.0,
+        );
+
+        assert_eq!(Color::WHITE, c1);
+        assert_eq!(Color::BLACK, c2);
+    }
+
+    #[test]
+    fn patterns_are_sampled_at_the_object_point() {
+        let m = Material {
+            pattern: Some(Arc::new(Stripe::new(Color::WHITE, Color::BLACK))),
+            ambient: 1.,
+            diffuse: 0.,
+            specular: 0.,
+            ..Default::default()
+        };
+        let light = Light::new(point(0, 0, -10), Color::WHITE);
+
+        let c = m.lighting(
+            &light,
+            point(0.5, 0, 0),
+            point(1.5, 0, 0),
+            vector(0, 0, -1),
+            vector(0, 0, -1),
+            1.0,
+        );
+
+        assert_eq!(Color::BLACK, c);
+    }
+
+    #[test]
+    fn materials_compare_patterns_by_identity() {
+        let stripes: Arc<dyn Pattern> = Arc::new(Stripe::new(Color::WHITE, Color::BLACK));
+        let m = Material {
+            pattern: Some(stripes.clone()),
+            ..Default::default()
+        };
+
+        assert_eq!(m, m.clone());
+        assert_ne!(
+            m,
+            Material {
+                pattern: Some(Arc::new(Stripe::new(Color::WHITE, Color::BLACK))),
+                ..Default::default()
+            }
+        );
+    }
 }
@@ -117,6 +117,8 @@ impl Submatrix<1> for BaseMatrix<2> {
     }
 
     #[inline(always)]
+    // `is_multiple_of` would need Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
     fn cofactor(&self, r: usize, c: usize) -> f64 {
         if (r + c) % 2 == 0 {
             self.minor(r, c)
@@ -157,6 +159,7 @@ macro_rules! impl_submatrix_for_square_matrix_n {
             }
 
             #[inline(always)]
+            #[allow(clippy::manual_is_multiple_of)]
             fn cofactor(&self, r: usize, c: usize) -> f64 {
                 if (r + c) % 2 == 0 {
                     self.minor(r, c)
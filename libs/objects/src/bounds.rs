@@ -0,0 +1,289 @@
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::{point, Tuple};
+
+// an axis-aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: Tuple,
+    pub max: Tuple,
+}
+
+// the `axis`th (x, y, z) component of a tuple
+pub(crate) fn component(t: Tuple, axis: usize) -> f64 {
+    [t.x(), t.y(), t.z()][axis]
+}
+
+impl Bounds {
+    pub fn new(min: Tuple, max: Tuple) -> Self {
+        Self { min, max }
+    }
+
+    // a box that contains nothing; adding to it gives just what was added
+    pub fn empty() -> Self {
+        Self::new(
+            point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        )
+    }
+
+    pub fn infinite() -> Self {
+        Self::new(
+            point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|a| component(self.min, a) > component(self.max, a))
+    }
+
+    pub fn is_finite(&self) -> bool {
+        (0..3).all(|a| component(self.min, a).is_finite() && component(self.max, a).is_finite())
+    }
+
+    pub fn add_point(&mut self, p: Tuple) {
+        self.min = point(
+            self.min.x().min(p.x()),
+            self.min.y().min(p.y()),
+            self.min.z().min(p.z()),
+        );
+        self.max = point(
+            self.max.x().max(p.x()),
+            self.max.y().max(p.y()),
+            self.max.z().max(p.z()),
+        );
+    }
+
+    // the smallest box containing both
+    pub fn merge(mut self, other: Bounds) -> Self {
+        if !other.is_empty() {
+            self.add_point(other.min);
+            self.add_point(other.max);
+        }
+        self
+    }
+
+    pub fn contains_point(&self, p: Tuple) -> bool {
+        (0..3).all(|a| {
+            let c = component(p, a);
+            component(self.min, a) <= c && c <= component(self.max, a)
+        })
+    }
+
+    pub fn contains_bounds(&self, other: &Bounds) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    // The box around this one after `transform`, e.g. into a parent's space. Boxes that
+    // reach infinity stay infinite, since their corners can't be transformed.
+    pub fn transform(&self, transform: BaseMatrix<4>) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        if !self.is_finite() {
+            return Self::infinite();
+        }
+
+        let (lo, hi) = (self.min, self.max);
+        let corners = [
+            point(lo.x(), lo.y(), lo.z()),
+            point(lo.x(), lo.y(), hi.z()),
+            point(lo.x(), hi.y(), lo.z()),
+            point(lo.x(), hi.y(), hi.z()),
+            point(hi.x(), lo.y(), lo.z()),
+            point(hi.x(), lo.y(), hi.z()),
+            point(hi.x(), hi.y(), lo.z()),
+            point(hi.x(), hi.y(), hi.z()),
+        ];
+
+        let mut bounds = Self::empty();
+        for corner in corners {
+            bounds.add_point(transform * corner);
+        }
+        bounds
+    }
+
+    // Whether the ray passes through the box anywhere along its line, behind its origin
+    // included, since shapes report hits there too.
+    pub fn intersects(&self, ray: Ray) -> bool {
+        let mut tmin = f64::NEG_INFINITY;
+        let mut tmax = f64::INFINITY;
+
+        for a in 0..3 {
+            let (origin, direction) = (component(ray.origin, a), component(ray.dir, a));
+            let (lo, hi) = (component(self.min, a), component(self.max, a));
+
+            // Parallel to this axis' slab: either always in it or never. Only exact zeros
+            // count, since rays inside hugely scaled groups can move very little per unit t.
+            if direction == 0. {
+                if origin < lo || origin > hi {
+                    return false;
+                }
+                continue;
+            }
+
+            let (t0, t1) = ((lo - origin) / direction, (hi - origin) / direction);
+            let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+            tmin = tmin.max(t0);
+            tmax = tmax.min(t1);
+
+            if tmin > tmax {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn centroid(&self) -> Tuple {
+        point(
+            (self.min.x() + self.max.x()) / 2.,
+            (self.min.y() + self.max.y()) / 2.,
+            (self.min.z() + self.max.z()) / 2.,
+        )
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.;
+        }
+        let d = self.max - self.min;
+        2. * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+#[cfg(test)]
+mod bounds_tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_4, SQRT_2};
+
+    use apolaki_tuple::vector;
+
+    use super::*;
+
+    #[test]
+    fn creating_an_empty_bounding_box() {
+        let b = Bounds::empty();
+
+        assert!(b.is_empty());
+        assert!(!b.contains_point(point(0, 0, 0)));
+    }
+
+    #[test]
+    fn adding_points_to_an_empty_bounding_box() {
+        let mut b = Bounds::empty();
+
+        b.add_point(point(-5, 2, 0));
+        b.add_point(point(7, 0, -3));
+
+        assert_eq!(point(-5, 0, -3), b.min);
+        assert_eq!(point(7, 2, 0), b.max);
+    }
+
+    #[test]
+    fn merging_one_bounding_box_with_another() {
+        let b1 = Bounds::new(point(-5, -2, 0), point(7, 4, 4));
+        let b2 = Bounds::new(point(8, -7, -2), point(14, 2, 8));
+
+        let b = b1.merge(b2);
+
+        assert_eq!(point(-5, -7, -2), b.min);
+        assert_eq!(point(14, 4, 8), b.max);
+        assert_eq!(b1, b1.merge(Bounds::empty()));
+    }
+
+    #[test]
+    fn checking_whether_a_box_contains_a_point_or_box() {
+        let b = Bounds::new(point(5, -2, 0), point(11, 4, 7));
+
+        assert!(b.contains_point(point(5, -2, 0)));
+        assert!(b.contains_point(point(8, 1, 3)));
+        assert!(!b.contains_point(point(3, 0, 3)));
+        assert!(!b.contains_point(point(8, 1, 8)));
+        assert!(b.contains_bounds(&Bounds::new(point(6, -1, 1), point(10, 3, 6))));
+        assert!(!b.contains_bounds(&Bounds::new(point(4, -3, -1), point(10, 3, 6))));
+    }
+
+    #[test]
+    fn transforming_a_bounding_box() {
+        let b = Bounds::new(point(-1, -1, -1), point(1, 1, 1));
+        let m =
+            BaseMatrix::identity().rotate_x(FRAC_PI_4) * BaseMatrix::identity().rotate_y(FRAC_PI_4);
+
+        let b2 = b.transform(m);
+
+        let h = 1. + FRAC_1_SQRT_2;
+        assert_eq!(point(-SQRT_2, -h, -h), b2.min);
+        assert_eq!(point(SQRT_2, h, h), b2.max);
+    }
+
+    #[test]
+    fn transformed_infinite_bounds_stay_infinite() {
+        let plane = Bounds::new(
+            point(f64::NEG_INFINITY, 0, f64::NEG_INFINITY),
+            point(f64::INFINITY, 0, f64::INFINITY),
+        );
+
+        let b = plane.transform(BaseMatrix::identity().translate(0, 1, 0));
+
+        assert!(!b.is_finite());
+        assert_eq!(f64::NEG_INFINITY, b.min.y());
+        assert_eq!(f64::INFINITY, b.max.y());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_bounding_box() {
+        let b = Bounds::new(point(5, -2, 0), point(11, 4, 7));
+        let cases = [
+            (point(15, 1, 2), vector(-1, 0, 0), true),
+            (point(-5, -1, 4), vector(1, 0, 0), true),
+            (point(7, 6, 5), vector(0, -1, 0), true),
+            (point(9, -5, 6), vector(0, 1, 0), true),
+            (point(8, 2, 12), vector(0, 0, -1), true),
+            (point(6, 0, -5), vector(0, 0, 1), true),
+            (point(8, 1, 3.5), vector(0, 0, 1), true),
+            (point(9, -1, -8), vector(2, 4, 6), false),
+            (point(8, 3, -4), vector(6, 2, 4), false),
+            (point(9, -1, -2), vector(4, 6, 2), false),
+            (point(4, 0, 9), vector(0, 0, -1), false),
+            (point(8, 6, -1), vector(0, -1, 0), false),
+            (point(12, 5, 4), vector(-1, 0, 0), false),
+        ];
+
+        for (origin, direction, result) in cases {
+            let r = Ray::new(origin, direction.normalize());
+
+            assert_eq!(result, b.intersects(r), "{origin:?} {direction:?}");
+        }
+    }
+
+    #[test]
+    fn rays_that_barely_move_along_an_axis_still_cross_its_slab() {
+        let b = Bounds::new(point(-1, -1, -1), point(1, 1, 1));
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1e-6));
+
+        assert!(b.intersects(r));
+    }
+
+    #[test]
+    fn rays_meet_infinite_bounds() {
+        let r = Ray::new(point(0, 5, 0), vector(1, 0, 0));
+
+        assert!(Bounds::infinite().intersects(r));
+    }
+
+    #[test]
+    fn surface_area_and_centroid() {
+        let b = Bounds::new(point(0, 0, 0), point(1, 2, 3));
+
+        assert_eq!(22., b.surface_area());
+        assert_eq!(point(0.5, 1, 1.5), b.centroid());
+        assert_eq!(0., Bounds::empty().surface_area());
+    }
+}
@@ -0,0 +1,292 @@
+use apolaki_ray::Ray;
+
+use crate::bounds::{component, Bounds};
+
+// how a node of the hierarchy is divided between its two children
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BvhSplit {
+    // wherever the surface-area heuristic says rays will test the fewest shapes; slower
+    // to build, faster to render
+    #[default]
+    SurfaceArea,
+    // in half by count, along the longest axis
+    Median,
+}
+
+// nodes with this many shapes or fewer aren't split any further
+const MAX_LEAF_SIZE: usize = 4;
+
+// A bounding volume hierarchy over a list of shapes, given by their bounds. It only hands
+// out indices into that list, so it works for anything that owns its shapes.
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    // indices of bounded shapes, ordered so each leaf's shapes are together
+    indices: Vec<usize>,
+    // shapes with no finite bounds, like planes, which every ray must be tested against
+    unbounded: Vec<usize>,
+    bounds: Bounds,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    bounds: Bounds,
+    kind: NodeKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    // a range of `indices`
+    Leaf { start: usize, end: usize },
+    Branch { left: usize, right: usize },
+}
+
+impl Bvh {
+    pub fn build(bounds: &[Bounds], split: BvhSplit) -> Self {
+        let (mut indices, unbounded): (Vec<_>, Vec<_>) =
+            (0..bounds.len()).partition(|&i| bounds[i].is_finite());
+        let mut bvh = Self {
+            bounds: bounds
+                .iter()
+                .fold(Bounds::empty(), |b, other| b.merge(*other)),
+            ..Default::default()
+        };
+
+        if !indices.is_empty() {
+            let len = indices.len();
+            bvh.build_node(bounds, &mut indices, 0, len, split);
+        }
+        bvh.indices = indices;
+        bvh.unbounded = unbounded;
+
+        bvh
+    }
+
+    // the bounds of every shape together
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    // the shapes whose bounds the ray passes through, and so might hit
+    pub fn candidates(&self, ray: Ray) -> Vec<usize> {
+        let mut result = self.unbounded.clone();
+        if self.nodes.is_empty() {
+            return result;
+        }
+
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = self.nodes[n];
+            if !node.bounds.intersects(ray) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf { start, end } => result.extend(&self.indices[start..end]),
+                NodeKind::Branch { left, right } => stack.extend([left, right]),
+            }
+        }
+
+        result
+    }
+
+    // how many levels deep the hierarchy goes
+    pub fn depth(&self) -> usize {
+        fn depth_of(bvh: &Bvh, n: usize) -> usize {
+            match bvh.nodes[n].kind {
+                NodeKind::Leaf { .. } => 1,
+                NodeKind::Branch { left, right } => {
+                    1 + depth_of(bvh, left).max(depth_of(bvh, right))
+                }
+            }
+        }
+
+        if self.nodes.is_empty() {
+            0
+        } else {
+            depth_of(self, 0)
+        }
+    }
+
+    // builds the node over `indices[start..end]`, reordering them, and returns its index
+    fn build_node(
+        &mut self,
+        bounds: &[Bounds],
+        indices: &mut [usize],
+        start: usize,
+        end: usize,
+        split: BvhSplit,
+    ) -> usize {
+        let node_bounds = indices[start..end]
+            .iter()
+            .fold(Bounds::empty(), |b, &i| b.merge(bounds[i]));
+        let n = self.nodes.len();
+        self.nodes.push(Node {
+            bounds: node_bounds,
+            kind: NodeKind::Leaf { start, end },
+        });
+
+        if end - start <= MAX_LEAF_SIZE {
+            return n;
+        }
+
+        let mid = match split {
+            BvhSplit::Median => median_split(bounds, &mut indices[start..end]),
+            BvhSplit::SurfaceArea => sah_split(bounds, &mut indices[start..end], &node_bounds),
+        };
+        let Some(mid) = mid else {
+            return n;
+        };
+
+        let left = self.build_node(bounds, indices, start, start + mid, split);
+        let right = self.build_node(bounds, indices, start + mid, end, split);
+        self.nodes[n].kind = NodeKind::Branch { left, right };
+
+        n
+    }
+}
+
+// sorts `indices` along the axis where their centroids are most spread out
+fn sort_along_longest_axis(bounds: &[Bounds], indices: &mut [usize]) {
+    let centroids = indices.iter().fold(Bounds::empty(), |mut b, &i| {
+        b.add_point(bounds[i].centroid());
+        b
+    });
+    let extent = centroids.max - centroids.min;
+    let axis = (0..3)
+        .max_by(|&a, &b| component(extent, a).total_cmp(&component(extent, b)))
+        .unwrap_or(0);
+
+    sort_along(bounds, indices, axis);
+}
+
+fn sort_along(bounds: &[Bounds], indices: &mut [usize], axis: usize) {
+    indices.sort_by(|&a, &b| {
+        component(bounds[a].centroid(), axis).total_cmp(&component(bounds[b].centroid(), axis))
+    });
+}
+
+fn median_split(bounds: &[Bounds], indices: &mut [usize]) -> Option<usize> {
+    sort_along_longest_axis(bounds, indices);
+    Some(indices.len() / 2)
+}
+
+// Picks the split, along any axis, that minimises the surface-area heuristic: the chance
+// of a ray entering each side (proportional to its area) times how many shapes it holds.
+// Returns `None` when no split beats leaving the node as a leaf.
+fn sah_split(bounds: &[Bounds], indices: &mut [usize], node_bounds: &Bounds) -> Option<usize> {
+    let len = indices.len();
+    let mut best: Option<(f64, usize, usize)> = None;
+
+    for axis in 0..3 {
+        sort_along(bounds, indices, axis);
+
+        // the area of everything right of each split, swept from the right
+        let mut right_areas = vec![0.; len];
+        let mut right = Bounds::empty();
+        for i in (1..len).rev() {
+            right = right.merge(bounds[indices[i]]);
+            right_areas[i] = right.surface_area();
+        }
+
+        let mut left = Bounds::empty();
+        for mid in 1..len {
+            left = left.merge(bounds[indices[mid - 1]]);
+            let cost = left.surface_area() * mid as f64 + right_areas[mid] * (len - mid) as f64;
+
+            if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                best = Some((cost, axis, mid));
+            }
+        }
+    }
+
+    let (cost, axis, mid) = best?;
+    // degenerate boxes (all in a plane, say) have no area, so fall back to the median
+    let leaf_cost = node_bounds.surface_area() * len as f64;
+    if leaf_cost > 0. && cost >= leaf_cost {
+        return None;
+    }
+    if leaf_cost == 0. {
+        return median_split(bounds, indices);
+    }
+
+    sort_along(bounds, indices, axis);
+    Some(mid)
+}
+
+#[cfg(test)]
+mod bvh_tests {
+    use apolaki_tuple::{point, vector};
+
+    use super::*;
+
+    // unit boxes in a row along x, one every 3 units
+    fn row_of_boxes(count: usize) -> Vec<Bounds> {
+        (0..count)
+            .map(|i| {
+                let x = 3. * i as f64;
+                Bounds::new(point(x, 0, 0), point(x + 1., 1, 1))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn an_empty_hierarchy_has_no_candidates() {
+        let bvh = Bvh::build(&[], BvhSplit::default());
+
+        assert!(bvh
+            .candidates(Ray::new(point(0, 0, 0), vector(0, 0, 1)))
+            .is_empty());
+        assert_eq!(0, bvh.depth());
+    }
+
+    #[test]
+    fn small_lists_fit_in_a_single_leaf() {
+        let bvh = Bvh::build(&row_of_boxes(MAX_LEAF_SIZE), BvhSplit::default());
+
+        assert_eq!(1, bvh.depth());
+    }
+
+    #[test]
+    fn large_lists_are_split() {
+        for split in [BvhSplit::SurfaceArea, BvhSplit::Median] {
+            let bvh = Bvh::build(&row_of_boxes(64), split);
+
+            assert!(bvh.depth() > 1);
+            assert_eq!(Bounds::new(point(0, 0, 0), point(190, 1, 1)), bvh.bounds());
+        }
+    }
+
+    #[test]
+    fn a_ray_only_gets_the_shapes_it_might_hit() {
+        for split in [BvhSplit::SurfaceArea, BvhSplit::Median] {
+            let bvh = Bvh::build(&row_of_boxes(64), split);
+            // straight down through the 11th box
+            let r = Ray::new(point(30.5, 5, 0.5), vector(0, -1, 0));
+
+            let candidates = bvh.candidates(r);
+
+            assert!(candidates.contains(&10));
+            assert!(candidates.len() <= MAX_LEAF_SIZE);
+        }
+    }
+
+    #[test]
+    fn a_ray_missing_everything_gets_no_candidates() {
+        let bvh = Bvh::build(&row_of_boxes(64), BvhSplit::default());
+        let r = Ray::new(point(30.5, 5, 5), vector(0, -1, 0));
+
+        assert!(bvh.candidates(r).is_empty());
+    }
+
+    #[test]
+    fn unbounded_shapes_are_always_candidates() {
+        let mut bounds = row_of_boxes(8);
+        bounds.push(Bounds::infinite());
+        let bvh = Bvh::build(&bounds, BvhSplit::default());
+        let r = Ray::new(point(30.5, 5, 5), vector(0, -1, 0));
+
+        assert_eq!(vec![8], bvh.candidates(r));
+        assert!(!bvh.bounds().is_finite());
+    }
+}
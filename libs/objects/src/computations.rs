@@ -0,0 +1,289 @@
+use std::ptr;
+
+use apolaki_ray::Ray;
+use apolaki_tuple::Tuple;
+
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
+
+// how far `over_point` and `under_point` are nudged off the surface, to keep them from
+// self-intersecting
+pub const EPSILON: f64 = 0.00001;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Computations<'a> {
+    pub t: f64,
+    pub obj: &'a dyn Shape,
+    pub point: Tuple,
+    pub eyev: Tuple,
+    pub normalv: Tuple,
+    pub inside: bool,
+    pub over_point: Tuple,
+    // `over_point` in the hit object's space, where its pattern is sampled
+    pub object_point: Tuple,
+    pub under_point: Tuple,
+    pub reflectv: Tuple,
+    // refractive indices of the materials on either side of the hit
+    pub n1: f64,
+    pub n2: f64,
+}
+
+impl Computations<'_> {
+    // Schlick's approximation of the Fresnel reflectance at the hit
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eyev.dot(self.normalv);
+
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n.powi(2) * (1. - cos.powi(2));
+            if sin2_t > 1. {
+                // total internal reflection
+                return 1.;
+            }
+            cos = (1. - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1. - r0) * (1. - cos).powi(5)
+    }
+}
+
+// `xs` is every intersection along the ray, used to find which materials the hit sits between
+pub fn prepare_computations<'a>(
+    intersection: &Intersection<'a>,
+    ray: &Ray,
+    xs: &Intersections<'a>,
+) -> Computations<'a> {
+    let t = intersection.t;
+    let obj = intersection.obj;
+    let point = ray.position(t);
+    let eyev = -ray.dir;
+    let normalv = obj.normal_at_hit(point, intersection);
+
+    // the eye is inside the shape when the normal points away from it
+    let (inside, normalv) = if normalv.dot(eyev) < 0. {
+        (true, -normalv)
+    } else {
+        (false, normalv)
+    };
+
+    let (n1, n2) = refractive_indices(intersection, xs);
+    let over_point = point + normalv * EPSILON;
+
+    Computations {
+        t,
+        obj,
+        point,
+        eyev,
+        normalv,
+        inside,
+        over_point,
+        object_point: intersection.world_to_object(over_point),
+        under_point: point - normalv * EPSILON,
+        reflectv: ray.dir.reflect(normalv),
+        n1,
+        n2,
+    }
+}
+
+// walks the intersections, tracking which objects the ray is currently inside
+fn refractive_indices(hit: &Intersection, xs: &Intersections) -> (f64, f64) {
+    fn outermost(containers: &[&dyn Shape]) -> f64 {
+        containers
+            .last()
+            .map_or(1., |obj| obj.material().refractive_index)
+    }
+
+    let mut containers: Vec<&dyn Shape> = Vec::new();
+    let mut n1 = 1.;
+
+    for i in xs.iter() {
+        let is_hit = i == hit;
+        if is_hit {
+            n1 = outermost(&containers);
+        }
+
+        match containers.iter().position(|&obj| ptr::addr_eq(obj, i.obj)) {
+            Some(index) => {
+                containers.remove(index);
+            }
+            None => containers.push(i.obj),
+        }
+
+        if is_hit {
+            return (n1, outermost(&containers));
+        }
+    }
+
+    (n1, 1.)
+}
+
+#[cfg(test)]
+mod computations_tests {
+    use std::f64::consts::SQRT_2;
+
+    use apolaki_matrix::BaseMatrix;
+    use apolaki_tuple::{point, vector};
+
+    use super::*;
+    use crate::Sphere;
+
+    #[test]
+    fn precomputing_the_state_of_an_intersection() {
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let shape = Sphere::default();
+        let i = Intersection::new(4, &shape);
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+
+        assert_eq!(i.t, comps.t);
+        assert!(std::ptr::addr_eq(i.obj, comps.obj));
+        assert_eq!(point(0, 0, -1), comps.point);
+        assert_eq!(vector(0, 0, -1), comps.eyev);
+        assert_eq!(vector(0, 0, -1), comps.normalv);
+    }
+
+    #[test]
+    fn the_hit_when_an_intersection_occurs_on_the_outside() {
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let shape = Sphere::default();
+        let i = Intersection::new(4, &shape);
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+
+        assert!(!comps.inside);
+    }
+
+    #[test]
+    fn the_hit_when_an_intersection_occurs_on_the_inside() {
+        let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
+        let shape = Sphere::default();
+        let i = Intersection::new(1, &shape);
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+
+        assert_eq!(point(0, 0, 1), comps.point);
+        assert_eq!(vector(0, 0, -1), comps.eyev);
+        assert!(comps.inside);
+        // normal would have been (0, 0, 1), but is inverted
+        assert_eq!(vector(0, 0, -1), comps.normalv);
+    }
+
+    #[test]
+    fn precomputing_the_reflection_vector() {
+        let r = Ray::new(point(0, SQRT_2 / 2., -5), vector(0, 0, 1));
+        let shape = Sphere::default();
+        let i = Intersection::new(5. - SQRT_2 / 2., &shape);
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+
+        assert_eq!(vector(0, 1, 0), comps.reflectv);
+    }
+
+    #[test]
+    fn finding_n1_and_n2_at_various_intersections() {
+        let glass = |transform, refractive_index| {
+            let mut s = Sphere::glass();
+            s.transform = transform;
+            s.material.refractive_index = refractive_index;
+            s
+        };
+        let a = glass(BaseMatrix::identity().scale(2, 2, 2), 1.5);
+        let b = glass(BaseMatrix::identity().translate(0, 0, -0.25), 2.0);
+        let c = glass(BaseMatrix::identity().translate(0, 0, 0.25), 2.5);
+        let r = Ray::new(point(0, 0, -4), vector(0, 0, 1));
+        let xs = Intersections::from(vec![
+            Intersection::new(2, &a),
+            Intersection::new(2.75, &b),
+            Intersection::new(3.25, &c),
+            Intersection::new(4.75, &b),
+            Intersection::new(5.25, &c),
+            Intersection::new(6, &a),
+        ]);
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+
+        for (index, (n1, n2)) in expected.into_iter().enumerate() {
+            let comps = prepare_computations(&xs[index], &r, &xs);
+
+            assert_eq!(n1, comps.n1);
+            assert_eq!(n2, comps.n2);
+        }
+    }
+
+    #[test]
+    fn the_under_point_is_offset_below_the_surface() {
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let shape = Sphere {
+            transform: BaseMatrix::identity().translate(0, 0, 1),
+            ..Sphere::glass()
+        };
+        let i = Intersection::new(5, &shape);
+        let xs = Intersections::from(vec![i]);
+
+        let comps = prepare_computations(&i, &r, &xs);
+
+        assert!(comps.under_point.z() > EPSILON / 2.);
+        assert!(comps.point.z() < comps.under_point.z());
+    }
+
+    #[test]
+    fn the_schlick_approximation_under_total_internal_reflection() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0, 0, SQRT_2 / 2.), vector(0, 1, 0));
+        let xs = Intersections::from(vec![
+            Intersection::new(-SQRT_2 / 2., &shape),
+            Intersection::new(SQRT_2 / 2., &shape),
+        ]);
+
+        let comps = prepare_computations(&xs[1], &r, &xs);
+
+        assert_eq!(1.0, comps.schlick());
+    }
+
+    #[test]
+    fn the_schlick_approximation_with_a_perpendicular_viewing_angle() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0, 0, 0), vector(0, 1, 0));
+        let xs = Intersections::from(vec![
+            Intersection::new(-1, &shape),
+            Intersection::new(1, &shape),
+        ]);
+
+        let comps = prepare_computations(&xs[1], &r, &xs);
+
+        assert!((comps.schlick() - 0.04).abs() < EPSILON);
+    }
+
+    #[test]
+    fn the_schlick_approximation_with_small_angle_and_n2_greater_than_n1() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0, 0.99, -2), vector(0, 0, 1));
+        let xs = Intersections::from(vec![Intersection::new(1.8589, &shape)]);
+
+        let comps = prepare_computations(&xs[0], &r, &xs);
+
+        assert!((comps.schlick() - 0.48873).abs() < EPSILON);
+    }
+
+    #[test]
+    fn the_hit_should_offset_the_point() {
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let shape = Sphere {
+            transform: BaseMatrix::identity().translate(0, 0, 1),
+            ..Default::default()
+        };
+        let i = Intersection::new(5, &shape);
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+
+        assert!(comps.over_point.z() < -EPSILON / 2.);
+        assert!(comps.point.z() > comps.over_point.z());
+    }
+}
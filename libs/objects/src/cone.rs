@@ -0,0 +1,220 @@
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::{point, vector, Tuple};
+
+use crate::bounds::Bounds;
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
+use crate::EPSILON;
+
+// a double-napped cone around the y axis, with its tip at the origin and a radius of |y|,
+// truncated to `minimum..maximum`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cone {
+    pub transform: BaseMatrix<4>,
+    pub material: Material,
+    pub minimum: f64,
+    pub maximum: f64,
+    // whether the ends are capped
+    pub closed: bool,
+}
+
+impl Cone {
+    pub fn new(minimum: f64, maximum: f64, closed: bool) -> Self {
+        Self {
+            minimum,
+            maximum,
+            closed,
+            ..Default::default()
+        }
+    }
+
+    // whether the ray is within the cap's radius, |y|, at `t`
+    fn check_cap(ray: Ray, t: f64, y: f64) -> bool {
+        let x = ray.origin.x() + t * ray.dir.x();
+        let z = ray.origin.z() + t * ray.dir.z();
+
+        x.powi(2) + z.powi(2) <= y.powi(2) + EPSILON
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || ray.dir.y().abs() < EPSILON {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin.y()) / ray.dir.y();
+            if Self::check_cap(ray, t, y) {
+                xs.push(Intersection::new(t, self));
+            }
+        }
+    }
+
+    fn push_if_in_bounds<'a>(&'a self, ray: Ray, t: f64, xs: &mut Vec<Intersection<'a>>) {
+        let y = ray.origin.y() + t * ray.dir.y();
+        if self.minimum < y && y < self.maximum {
+            xs.push(Intersection::new(t, self));
+        }
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self {
+            transform: BaseMatrix::identity(),
+            material: Material::default(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Shape for Cone {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    // the widest the cone gets is at whichever end is furthest from its tip
+    fn bounds(&self) -> Bounds {
+        let limit = self.minimum.abs().max(self.maximum.abs());
+        Bounds::new(
+            point(-limit, self.minimum, -limit),
+            point(limit, self.maximum, limit),
+        )
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let (o, d) = (ray.origin, ray.dir);
+        let a = d.x().powi(2) - d.y().powi(2) + d.z().powi(2);
+        let b = 2. * o.x() * d.x() - 2. * o.y() * d.y() + 2. * o.z() * d.z();
+        let c = o.x().powi(2) - o.y().powi(2) + o.z().powi(2);
+        let mut xs = Vec::new();
+
+        if a.abs() < EPSILON {
+            // the ray is parallel to one of the halves, so it crosses the other one once
+            if b.abs() >= EPSILON {
+                self.push_if_in_bounds(ray, -c / (2. * b), &mut xs);
+            }
+        } else {
+            let discriminant = b.powi(2) - 4. * a * c;
+
+            if discriminant >= 0. {
+                let t0 = (-b - discriminant.sqrt()) / (2. * a);
+                let t1 = (-b + discriminant.sqrt()) / (2. * a);
+                let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+
+                self.push_if_in_bounds(ray, t0, &mut xs);
+                self.push_if_in_bounds(ray, t1, &mut xs);
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+
+        if xs.is_empty() {
+            None
+        } else {
+            Some(Intersections::from(xs))
+        }
+    }
+
+    fn local_normal_at(&self, at: Tuple) -> Tuple {
+        let dist = at.x().powi(2) + at.z().powi(2);
+
+        if dist < at.y().powi(2) && at.y() >= self.maximum - EPSILON {
+            vector(0, 1, 0)
+        } else if dist < at.y().powi(2) && at.y() <= self.minimum + EPSILON {
+            vector(0, -1, 0)
+        } else {
+            let y = if at.y() > 0. {
+                -dist.sqrt()
+            } else {
+                dist.sqrt()
+            };
+            vector(at.x(), y, at.z())
+        }
+    }
+}
+
+#[cfg(test)]
+mod cone_tests {
+    use std::f64::consts::SQRT_2;
+
+    use super::*;
+
+    #[test]
+    fn intersecting_a_cone_with_a_ray() {
+        let shape = Cone::default();
+        let cases = [
+            (point(0, 0, -5), vector(0, 0, 1), 5., 5.),
+            (point(0, 0, -5), vector(1, 1, 1), 8.66025, 8.66025),
+            (point(1, 1, -5), vector(-0.5, -1, 1), 4.55006, 49.44994),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let r = Ray::new(origin, direction.normalize());
+
+            let xs = shape.local_intersect(r).expect("must have intersections");
+
+            assert_eq!(2, xs.len());
+            assert!((t0 - xs[0].t).abs() < EPSILON);
+            assert!((t1 - xs[1].t).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn intersecting_a_cone_with_a_ray_parallel_to_one_of_its_halves() {
+        let shape = Cone::default();
+        let r = Ray::new(point(0, 0, -1), vector(0, 1, 1).normalize());
+
+        let xs = shape.local_intersect(r).expect("must have intersections");
+
+        assert_eq!(1, xs.len());
+        assert!((0.35355 - xs[0].t).abs() < EPSILON);
+    }
+
+    #[test]
+    fn intersecting_a_cones_end_caps() {
+        let shape = Cone::new(-0.5, 0.5, true);
+        let cases = [
+            (point(0, 0, -5), vector(0, 1, 0), 0),
+            (point(0, 0, -0.25), vector(0, 1, 1), 2),
+            (point(0, 0, -0.25), vector(0, 1, 0), 4),
+        ];
+
+        for (origin, direction, count) in cases {
+            let r = Ray::new(origin, direction.normalize());
+
+            let xs = shape.local_intersect(r).unwrap_or_default();
+
+            assert_eq!(count, xs.len());
+        }
+    }
+
+    #[test]
+    fn computing_the_normal_vector_on_a_cone() {
+        let shape = Cone::default();
+        let cases = [
+            (point(0, 0, 0), vector(0, 0, 0)),
+            (point(1, 1, 1), vector(1, -SQRT_2, 1)),
+            (point(-1, -1, 0), vector(-1, 1, 0)),
+        ];
+
+        for (at, normal) in cases {
+            assert_eq!(normal, shape.local_normal_at(at));
+        }
+    }
+
+    #[test]
+    fn the_normal_vector_on_a_cones_end_caps() {
+        let shape = Cone::new(-1., 2., true);
+
+        assert_eq!(vector(0, 1, 0), shape.local_normal_at(point(0.5, 2, 0)));
+        assert_eq!(vector(0, -1, 0), shape.local_normal_at(point(0, -1, 0.5)));
+    }
+}
@@ -0,0 +1,285 @@
+use std::ptr;
+
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::Tuple;
+
+use crate::bounds::Bounds;
+use crate::intersect::Intersections;
+use crate::shape::Shape;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CsgOperation {
+    // everything in either shape
+    Union,
+    // only what is in both shapes
+    Intersection,
+    // what is in the left shape but not the right
+    Difference,
+}
+
+// Whether a hit survives the operation. `left_hit` says which shape was hit, and `in_left`
+// and `in_right` whether the ray was inside each shape at the time.
+pub fn intersection_allowed(
+    operation: CsgOperation,
+    left_hit: bool,
+    in_left: bool,
+    in_right: bool,
+) -> bool {
+    match operation {
+        CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+        CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+        CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left),
+    }
+}
+
+// constructive solid geometry: two shapes combined by an operation
+#[derive(Debug)]
+pub struct Csg {
+    pub operation: CsgOperation,
+    pub transform: BaseMatrix<4>,
+    left: Box<dyn Shape>,
+    right: Box<dyn Shape>,
+    // like groups, only the shapes inside are ever shaded
+    material: Material,
+}
+
+impl Csg {
+    pub fn new<L, R>(operation: CsgOperation, left: L, right: R) -> Self
+    where
+        L: Shape + 'static,
+        R: Shape + 'static,
+    {
+        Self {
+            operation,
+            transform: BaseMatrix::identity(),
+            left: Box::new(left),
+            right: Box::new(right),
+            material: Material::default(),
+        }
+    }
+
+    pub fn left(&self) -> &dyn Shape {
+        self.left.as_ref()
+    }
+
+    pub fn right(&self) -> &dyn Shape {
+        self.right.as_ref()
+    }
+
+    // Keeps only the hits on the surface of the combined shape. `xs` must be sorted, so
+    // that walking it tracks whether the ray is inside each shape.
+    pub fn filter_intersections<'a>(&self, xs: Intersections<'a>) -> Intersections<'a> {
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut result = Vec::new();
+
+        for i in xs {
+            let left_hit = self.left.includes(i.obj);
+
+            if intersection_allowed(self.operation, left_hit, in_left, in_right) {
+                result.push(i);
+            }
+
+            if left_hit {
+                in_left = !in_left;
+            } else {
+                in_right = !in_right;
+            }
+        }
+
+        Intersections::from(result)
+    }
+}
+
+impl Shape for Csg {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.left
+            .parent_space_bounds()
+            .merge(self.right.parent_space_bounds())
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        ptr::addr_eq(self, other) || self.left.includes(other) || self.right.includes(other)
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let xs: Intersections = [self.left.intersect(ray), self.right.intersect(ray)]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|i| i.within_group(self.transform))
+            .collect();
+
+        let xs = self.filter_intersections(xs);
+        if xs.is_empty() {
+            None
+        } else {
+            Some(xs)
+        }
+    }
+
+    fn local_normal_at(&self, _at: Tuple) -> Tuple {
+        panic!("CSG shapes have no surface of their own; ask the child that was hit")
+    }
+}
+
+#[cfg(test)]
+mod csg_tests {
+    use apolaki_tuple::{point, vector};
+
+    use super::*;
+    use crate::{parse_obj, Cube, Group, Intersection, Sphere};
+
+    #[test]
+    fn csg_is_created_with_an_operation_and_two_shapes() {
+        let c = Csg::new(CsgOperation::Union, Sphere::default(), Cube::default());
+
+        assert_eq!(CsgOperation::Union, c.operation);
+        assert!(c.includes(c.left()));
+        assert!(c.includes(c.right()));
+        assert!(!c.left().includes(c.right()));
+    }
+
+    #[test]
+    fn evaluating_the_rule_for_a_csg_operation() {
+        use CsgOperation::*;
+        let cases = [
+            (Union, true, true, true, false),
+            (Union, true, true, false, true),
+            (Union, true, false, true, false),
+            (Union, true, false, false, true),
+            (Union, false, true, true, false),
+            (Union, false, true, false, false),
+            (Union, false, false, true, true),
+            (Union, false, false, false, true),
+            (Intersection, true, true, true, true),
+            (Intersection, true, true, false, false),
+            (Intersection, true, false, true, true),
+            (Intersection, true, false, false, false),
+            (Intersection, false, true, true, true),
+            (Intersection, false, true, false, true),
+            (Intersection, false, false, true, false),
+            (Intersection, false, false, false, false),
+            (Difference, true, true, true, false),
+            (Difference, true, true, false, true),
+            (Difference, true, false, true, false),
+            (Difference, true, false, false, true),
+            (Difference, false, true, true, true),
+            (Difference, false, true, false, true),
+            (Difference, false, false, true, false),
+            (Difference, false, false, false, false),
+        ];
+
+        for (op, left_hit, in_left, in_right, allowed) in cases {
+            assert_eq!(
+                allowed,
+                intersection_allowed(op, left_hit, in_left, in_right),
+                "{op:?} lhit={left_hit} inl={in_left} inr={in_right}"
+            );
+        }
+    }
+
+    #[test]
+    fn filtering_a_list_of_intersections() {
+        let cases = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+
+        for (op, x0, x1) in cases {
+            let c = Csg::new(op, Sphere::default(), Cube::default());
+            let xs = Intersections::from(vec![
+                Intersection::new(1, c.left()),
+                Intersection::new(2, c.right()),
+                Intersection::new(3, c.left()),
+                Intersection::new(4, c.right()),
+            ]);
+
+            let result = c.filter_intersections(xs.clone());
+
+            assert_eq!(2, result.len());
+            assert_eq!(xs[x0], result[0]);
+            assert_eq!(xs[x1], result[1]);
+        }
+    }
+
+    #[test]
+    fn a_ray_misses_a_csg_object() {
+        let c = Csg::new(CsgOperation::Union, Sphere::default(), Cube::default());
+        let r = Ray::new(point(0, 2, -5), vector(0, 0, 1));
+
+        assert_eq!(None, c.local_intersect(r));
+    }
+
+    #[test]
+    fn a_ray_hits_a_csg_object() {
+        let s2 = Sphere {
+            transform: BaseMatrix::identity().translate(0, 0, 0.5),
+            ..Default::default()
+        };
+        let c = Csg::new(CsgOperation::Union, Sphere::default(), s2);
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+
+        let xs = c.local_intersect(r).expect("must have intersections");
+
+        assert_eq!(2, xs.len());
+        assert_eq!(4., xs[0].t);
+        assert!(ptr::addr_eq(c.left(), xs[0].obj));
+        assert_eq!(6.5, xs[1].t);
+        assert!(ptr::addr_eq(c.right(), xs[1].obj));
+    }
+
+    #[test]
+    fn a_difference_drills_a_hole() {
+        // a cube with a stretched sphere bored out through its middle
+        let bore = Sphere {
+            transform: BaseMatrix::identity().scale(0.5, 0.5, 2),
+            ..Default::default()
+        };
+        let c = Csg::new(CsgOperation::Difference, Cube::default(), bore);
+
+        let through_hole = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let beside_hole = Ray::new(point(0.75, 0, -5), vector(0, 0, 1));
+
+        assert_eq!(None, c.intersect(through_hole));
+        let xs = c.intersect(beside_hole).expect("must hit the solid part");
+        assert_eq!(4., xs[0].t);
+        assert_eq!(
+            vector(0, 0, -1),
+            xs[0].obj.normal_at_hit(beside_hole.position(4.), &xs[0])
+        );
+    }
+
+    #[test]
+    fn mesh_hits_are_kept_on_the_left_of_a_difference() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+f 1 2 3";
+        let mesh = Group::from(parse_obj(file).expect("must parse"));
+        // a sphere the ray has already passed before it meets the mesh
+        let far_away = Sphere {
+            transform: BaseMatrix::identity().translate(0, 0, -10),
+            ..Default::default()
+        };
+        let c = Csg::new(CsgOperation::Difference, mesh, far_away);
+        let r = Ray::new(point(0, 0.5, -2), vector(0, 0, 1));
+
+        let xs = c.intersect(r).expect("must hit the mesh");
+
+        assert_eq!(1, xs.len());
+        assert_eq!(2., xs[0].t);
+        assert!(c.left().includes(xs[0].obj));
+    }
+}
@@ -0,0 +1,161 @@
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::{point, vector, Tuple};
+
+use crate::bounds::Bounds;
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
+use crate::EPSILON;
+
+// an axis-aligned cube spanning -1 to 1 on every axis
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cube {
+    pub transform: BaseMatrix<4>,
+    pub material: Material,
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Self {
+            transform: BaseMatrix::identity(),
+            material: Material::default(),
+        }
+    }
+}
+
+// where the ray enters and leaves the slab between -1 and 1 on one axis
+fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
+    let tmin_numerator = -1. - origin;
+    let tmax_numerator = 1. - origin;
+
+    // a ray parallel to the slab never crosses it; infinities keep it out of the way
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+impl Shape for Cube {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(point(-1, -1, -1), point(1, 1, 1))
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let (xtmin, xtmax) = check_axis(ray.origin.x(), ray.dir.x());
+        let (ytmin, ytmax) = check_axis(ray.origin.y(), ray.dir.y());
+        let (ztmin, ztmax) = check_axis(ray.origin.z(), ray.dir.z());
+
+        // the ray is inside the cube only while it is inside all three slabs
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            None
+        } else {
+            Some(Intersections::from(vec![
+                Intersection::new(tmin, self),
+                Intersection::new(tmax, self),
+            ]))
+        }
+    }
+
+    // the face a point is on is the axis of its largest component
+    fn local_normal_at(&self, at: Tuple) -> Tuple {
+        let (x, y, z) = (at.x(), at.y(), at.z());
+        let maxc = x.abs().max(y.abs()).max(z.abs());
+
+        if maxc == x.abs() {
+            vector(x, 0, 0)
+        } else if maxc == y.abs() {
+            vector(0, y, 0)
+        } else {
+            vector(0, 0, z)
+        }
+    }
+}
+
+#[cfg(test)]
+mod cube_tests {
+    use super::*;
+
+    #[test]
+    fn a_ray_intersects_a_cube() {
+        let c = Cube::default();
+        let cases = [
+            (point(5, 0.5, 0), vector(-1, 0, 0), 4., 6.),
+            (point(-5, 0.5, 0), vector(1, 0, 0), 4., 6.),
+            (point(0.5, 5, 0), vector(0, -1, 0), 4., 6.),
+            (point(0.5, -5, 0), vector(0, 1, 0), 4., 6.),
+            (point(0.5, 0, 5), vector(0, 0, -1), 4., 6.),
+            (point(0.5, 0, -5), vector(0, 0, 1), 4., 6.),
+            (point(0, 0.5, 0), vector(0, 0, 1), -1., 1.),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            let r = Ray::new(origin, direction);
+
+            let xs = c.local_intersect(r).expect("must have intersections");
+
+            assert_eq!(2, xs.len());
+            assert_eq!(t1, xs[0].t);
+            assert_eq!(t2, xs[1].t);
+        }
+    }
+
+    #[test]
+    fn a_ray_misses_a_cube() {
+        let c = Cube::default();
+        let cases = [
+            (point(-2, 0, 0), vector(0.2673, 0.5345, 0.8018)),
+            (point(0, -2, 0), vector(0.8018, 0.2673, 0.5345)),
+            (point(0, 0, -2), vector(0.5345, 0.8018, 0.2673)),
+            (point(2, 0, 2), vector(0, 0, -1)),
+            (point(0, 2, 2), vector(0, -1, 0)),
+            (point(2, 2, 0), vector(-1, 0, 0)),
+        ];
+
+        for (origin, direction) in cases {
+            let r = Ray::new(origin, direction);
+
+            assert_eq!(None, c.local_intersect(r));
+        }
+    }
+
+    #[test]
+    fn the_normal_on_the_surface_of_a_cube() {
+        let c = Cube::default();
+        let cases = [
+            (point(1, 0.5, -0.8), vector(1, 0, 0)),
+            (point(-1, -0.2, 0.9), vector(-1, 0, 0)),
+            (point(-0.4, 1, -0.1), vector(0, 1, 0)),
+            (point(0.3, -1, -0.7), vector(0, -1, 0)),
+            (point(-0.6, 0.3, 1), vector(0, 0, 1)),
+            (point(0.4, 0.4, -1), vector(0, 0, -1)),
+            (point(1, 1, 1), vector(1, 0, 0)),
+            (point(-1, -1, -1), vector(-1, 0, 0)),
+        ];
+
+        for (at, normal) in cases {
+            assert_eq!(normal, c.local_normal_at(at));
+        }
+    }
+}
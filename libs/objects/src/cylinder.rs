@@ -0,0 +1,254 @@
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::{point, vector, Tuple};
+
+use crate::bounds::Bounds;
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
+use crate::EPSILON;
+
+// a unit-radius cylinder around the y axis, truncated to `minimum..maximum`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cylinder {
+    pub transform: BaseMatrix<4>,
+    pub material: Material,
+    pub minimum: f64,
+    pub maximum: f64,
+    // whether the ends are capped
+    pub closed: bool,
+}
+
+impl Cylinder {
+    pub fn new(minimum: f64, maximum: f64, closed: bool) -> Self {
+        Self {
+            minimum,
+            maximum,
+            closed,
+            ..Default::default()
+        }
+    }
+
+    // whether the ray is within the unit radius at `t`
+    fn check_cap(ray: Ray, t: f64) -> bool {
+        let x = ray.origin.x() + t * ray.dir.x();
+        let z = ray.origin.z() + t * ray.dir.z();
+
+        x.powi(2) + z.powi(2) <= 1.
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: Ray, xs: &mut Vec<Intersection<'a>>) {
+        // caps only matter when closed, and only a ray that isn't parallel to them can hit
+        if !self.closed || ray.dir.y().abs() < EPSILON {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin.y()) / ray.dir.y();
+            if Self::check_cap(ray, t) {
+                xs.push(Intersection::new(t, self));
+            }
+        }
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self {
+            transform: BaseMatrix::identity(),
+            material: Material::default(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Shape for Cylinder {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(point(-1, self.minimum, -1), point(1, self.maximum, 1))
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let mut xs = Vec::new();
+        let a = ray.dir.x().powi(2) + ray.dir.z().powi(2);
+
+        // rays parallel to the y axis can only hit the caps
+        if a.abs() >= EPSILON {
+            let b = 2. * ray.origin.x() * ray.dir.x() + 2. * ray.origin.z() * ray.dir.z();
+            let c = ray.origin.x().powi(2) + ray.origin.z().powi(2) - 1.;
+            let discriminant = b.powi(2) - 4. * a * c;
+
+            if discriminant < 0. {
+                return None;
+            }
+
+            for t in [
+                (-b - discriminant.sqrt()) / (2. * a),
+                (-b + discriminant.sqrt()) / (2. * a),
+            ] {
+                let y = ray.origin.y() + t * ray.dir.y();
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+
+        if xs.is_empty() {
+            None
+        } else {
+            Some(Intersections::from(xs))
+        }
+    }
+
+    fn local_normal_at(&self, at: Tuple) -> Tuple {
+        let dist = at.x().powi(2) + at.z().powi(2);
+
+        if dist < 1. && at.y() >= self.maximum - EPSILON {
+            vector(0, 1, 0)
+        } else if dist < 1. && at.y() <= self.minimum + EPSILON {
+            vector(0, -1, 0)
+        } else {
+            vector(at.x(), 0, at.z())
+        }
+    }
+}
+
+#[cfg(test)]
+mod cylinder_tests {
+    use super::*;
+
+    #[test]
+    fn a_ray_misses_a_cylinder() {
+        let cyl = Cylinder::default();
+        let cases = [
+            (point(1, 0, 0), vector(0, 1, 0)),
+            (point(0, 0, 0), vector(0, 1, 0)),
+            (point(0, 0, -5), vector(1, 1, 1)),
+        ];
+
+        for (origin, direction) in cases {
+            let r = Ray::new(origin, direction.normalize());
+
+            assert_eq!(None, cyl.local_intersect(r));
+        }
+    }
+
+    #[test]
+    fn a_ray_strikes_a_cylinder() {
+        let cyl = Cylinder::default();
+        let cases = [
+            (point(1, 0, -5), vector(0, 0, 1), 5., 5.),
+            (point(0, 0, -5), vector(0, 0, 1), 4., 6.),
+            (point(0.5, 0, -5), vector(0.1, 1, 1), 6.80798, 7.08872),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let r = Ray::new(origin, direction.normalize());
+
+            let xs = cyl.local_intersect(r).expect("must have intersections");
+
+            assert_eq!(2, xs.len());
+            assert!((t0 - xs[0].t).abs() < EPSILON);
+            assert!((t1 - xs[1].t).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn normal_vector_on_a_cylinder() {
+        let cyl = Cylinder::default();
+        let cases = [
+            (point(1, 0, 0), vector(1, 0, 0)),
+            (point(0, 5, -1), vector(0, 0, -1)),
+            (point(0, -2, 1), vector(0, 0, 1)),
+            (point(-1, 1, 0), vector(-1, 0, 0)),
+        ];
+
+        for (at, normal) in cases {
+            assert_eq!(normal, cyl.local_normal_at(at));
+        }
+    }
+
+    #[test]
+    fn the_default_minimum_and_maximum_for_a_cylinder() {
+        let cyl = Cylinder::default();
+
+        assert_eq!(f64::NEG_INFINITY, cyl.minimum);
+        assert_eq!(f64::INFINITY, cyl.maximum);
+    }
+
+    #[test]
+    fn intersecting_a_constrained_cylinder() {
+        let cyl = Cylinder::new(1., 2., false);
+        let cases = [
+            (point(0, 1.5, 0), vector(0.1, 1, 0), 0),
+            (point(0, 3, -5), vector(0, 0, 1), 0),
+            (point(0, 0, -5), vector(0, 0, 1), 0),
+            (point(0, 2, -5), vector(0, 0, 1), 0),
+            (point(0, 1, -5), vector(0, 0, 1), 0),
+            (point(0, 1.5, -2), vector(0, 0, 1), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let r = Ray::new(origin, direction.normalize());
+
+            let xs = cyl.local_intersect(r).unwrap_or_default();
+
+            assert_eq!(count, xs.len());
+        }
+    }
+
+    #[test]
+    fn the_default_closed_value_for_a_cylinder() {
+        let cyl = Cylinder::default();
+
+        assert!(!cyl.closed);
+    }
+
+    #[test]
+    fn intersecting_the_caps_of_a_closed_cylinder() {
+        let cyl = Cylinder::new(1., 2., true);
+        let cases = [
+            (point(0, 3, 0), vector(0, -1, 0)),
+            (point(0, 3, -2), vector(0, -1, 2)),
+            (point(0, 4, -2), vector(0, -1, 1)),
+            (point(0, 0, -2), vector(0, 1, 2)),
+            (point(0, -1, -2), vector(0, 1, 1)),
+        ];
+
+        for (origin, direction) in cases {
+            let r = Ray::new(origin, direction.normalize());
+
+            let xs = cyl.local_intersect(r).expect("must have intersections");
+
+            assert_eq!(2, xs.len());
+        }
+    }
+
+    #[test]
+    fn the_normal_vector_on_a_cylinders_end_caps() {
+        let cyl = Cylinder::new(1., 2., true);
+        let cases = [
+            (point(0, 1, 0), vector(0, -1, 0)),
+            (point(0.5, 1, 0), vector(0, -1, 0)),
+            (point(0, 1, 0.5), vector(0, -1, 0)),
+            (point(0, 2, 0), vector(0, 1, 0)),
+            (point(0.5, 2, 0), vector(0, 1, 0)),
+            (point(0, 2, 0.5), vector(0, 1, 0)),
+        ];
+
+        for (at, normal) in cases {
+            assert_eq!(normal, cyl.local_normal_at(at));
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use std::ptr;
+use std::sync::OnceLock;
+
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::Tuple;
+
+use crate::bounds::Bounds;
+use crate::bvh::{Bvh, BvhSplit};
+use crate::intersect::Intersections;
+use crate::shape::Shape;
+
+// a collection of shapes that are transformed together
+#[derive(Debug)]
+pub struct Group {
+    pub transform: BaseMatrix<4>,
+    children: Vec<Box<dyn Shape>>,
+    // groups are never shaded themselves; only their children are
+    material: Material,
+    bvh_split: BvhSplit,
+    // built over the children the first time it's needed
+    bvh: OnceLock<Bvh>,
+}
+
+impl Group {
+    pub fn new(transform: BaseMatrix<4>) -> Self {
+        Self {
+            transform,
+            ..Default::default()
+        }
+    }
+
+    pub fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+
+    pub fn add_child<S: Shape + 'static>(&mut self, child: S) {
+        self.children.push(Box::new(child));
+        self.bvh.take();
+    }
+
+    pub fn bvh_split(&self) -> BvhSplit {
+        self.bvh_split
+    }
+
+    pub fn set_bvh_split(&mut self, split: BvhSplit) {
+        self.bvh_split = split;
+        self.bvh.take();
+    }
+
+    // the hierarchy over the children's bounds, in group space
+    pub fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| {
+            let bounds: Vec<_> = self
+                .children
+                .iter()
+                .map(|child| child.parent_space_bounds())
+                .collect();
+            Bvh::build(&bounds, self.bvh_split)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            transform: BaseMatrix::identity(),
+            children: Vec::new(),
+            material: Material::default(),
+            bvh_split: BvhSplit::default(),
+            bvh: OnceLock::new(),
+        }
+    }
+}
+
+impl Shape for Group {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.bvh().bounds()
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        ptr::addr_eq(self, other) || self.children.iter().any(|child| child.includes(other))
+    }
+
+    // children are intersected in group space, and their hits remember this group's
+    // transform so their normals can be brought back out through it
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let xs: Intersections = self
+            .bvh()
+            .candidates(ray)
+            .into_iter()
+            .filter_map(|i| self.children[i].intersect(ray))
+            .flatten()
+            .map(|i| i.within_group(self.transform))
+            .collect();
+
+        if xs.is_empty() {
+            None
+        } else {
+            Some(xs)
+        }
+    }
+
+    fn local_normal_at(&self, _at: Tuple) -> Tuple {
+        panic!("groups have no surface of their own; ask the child that was hit")
+    }
+}
+
+#[cfg(test)]
+mod group_tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use apolaki_tuple::{point, vector};
+
+    use super::*;
+    use crate::{prepare_computations, Cylinder, Intersection, Sphere};
+
+    #[test]
+    fn creating_a_new_group() {
+        let g = Group::default();
+
+        assert_eq!(BaseMatrix::identity(), g.transform);
+        assert!(g.is_empty());
+    }
+
+    #[test]
+    fn adding_a_child_to_a_group() {
+        let mut g = Group::default();
+
+        g.add_child(Sphere::default());
+
+        assert_eq!(1, g.children().len());
+    }
+
+    #[test]
+    fn a_group_includes_its_descendants() {
+        let mut inner = Group::default();
+        inner.add_child(Sphere::default());
+        let mut outer = Group::default();
+        outer.add_child(inner);
+        let other = Sphere::default();
+
+        let nested = outer.children()[0].as_ref();
+        let sphere = match nested.intersect(Ray::new(point(0, 0, -5), vector(0, 0, 1))) {
+            Some(xs) => xs[0].obj,
+            None => panic!("must hit the nested sphere"),
+        };
+
+        assert!(outer.includes(nested));
+        assert!(outer.includes(sphere));
+        assert!(!outer.includes(&other));
+    }
+
+    #[test]
+    fn a_groups_bounds_contain_its_children() {
+        let mut g = Group::default();
+        g.add_child(Sphere {
+            transform: BaseMatrix::identity().scale(2, 2, 2).translate(2, 5, -3),
+            ..Default::default()
+        });
+        g.add_child(Cylinder {
+            transform: BaseMatrix::identity()
+                .scale(0.5, 1, 0.5)
+                .translate(-4, -1, 4),
+            ..Cylinder::new(-2., 2., false)
+        });
+
+        let b = g.bounds();
+
+        assert_eq!(point(-4.5, -3, -5), b.min);
+        assert_eq!(point(4, 7, 4.5), b.max);
+    }
+
+    #[test]
+    fn large_groups_split_their_children_into_a_hierarchy() {
+        let mut g = Group::default();
+        for i in 0..100 {
+            g.add_child(Sphere {
+                transform: BaseMatrix::identity().translate(3 * i, 0, 0),
+                ..Default::default()
+            });
+        }
+        let r = Ray::new(point(30, 0, -5), vector(0, 0, 1));
+
+        let xs = g.intersect(r).expect("must have intersections");
+
+        assert!(g.bvh().depth() > 1);
+        assert_eq!(2, xs.len());
+        assert!(std::ptr::addr_eq(xs[0].obj, g.children()[10].as_ref()));
+    }
+
+    #[test]
+    fn adding_a_child_rebuilds_the_hierarchy() {
+        let mut g = Group::default();
+        g.add_child(Sphere::default());
+        let r = Ray::new(point(5, 0, -5), vector(0, 0, 1));
+        assert_eq!(None, g.intersect(r));
+
+        g.add_child(Sphere {
+            transform: BaseMatrix::identity().translate(5, 0, 0),
+            ..Default::default()
+        });
+
+        assert!(g.intersect(r).is_some());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_an_empty_group() {
+        let g = Group::default();
+        let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
+
+        assert_eq!(None, g.local_intersect(r));
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_nonempty_group() {
+        let mut g = Group::default();
+        g.add_child(Sphere::default());
+        g.add_child(Sphere {
+            transform: BaseMatrix::identity().translate(0, 0, -3),
+            ..Default::default()
+        });
+        g.add_child(Sphere {
+            transform: BaseMatrix::identity().translate(5, 0, 0),
+            ..Default::default()
+        });
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+
+        let xs = g.local_intersect(r).expect("must have intersections");
+
+        let (s1, s2) = (g.children()[0].as_ref(), g.children()[1].as_ref());
+        assert_eq!(4, xs.len());
+        assert!(std::ptr::addr_eq(xs[0].obj, s2));
+        assert!(std::ptr::addr_eq(xs[1].obj, s2));
+        assert!(std::ptr::addr_eq(xs[2].obj, s1));
+        assert!(std::ptr::addr_eq(xs[3].obj, s1));
+    }
+
+    #[test]
+    fn intersecting_a_hugely_scaled_group() {
+        // in group space the ray moves only 1e-6 along z per unit of t
+        let mut g = Group::new(BaseMatrix::identity().scale(1e6, 1e6, 1e6));
+        g.add_child(Sphere::default());
+        let r = Ray::new(point(0, 0, -5e6), vector(0, 0, 1));
+
+        let xs = g.intersect(r).expect("must have intersections");
+
+        assert_eq!(2, xs.len());
+        assert!((xs[0].t - 4e6).abs() < 1e-3);
+        assert!((xs[1].t - 6e6).abs() < 1e-3);
+    }
+
+    #[test]
+    fn intersecting_a_transformed_group() {
+        let mut g = Group::new(BaseMatrix::identity().scale(2, 2, 2));
+        g.add_child(Sphere {
+            transform: BaseMatrix::identity().translate(5, 0, 0),
+            ..Default::default()
+        });
+        let r = Ray::new(point(10, 0, -10), vector(0, 0, 1));
+
+        let xs = g.intersect(r).expect("must have intersections");
+
+        assert_eq!(2, xs.len());
+    }
+
+    // the combined transform of two nested groups, and a sphere inside the inner one
+    fn nested_sphere(inner_scale: (f64, f64, f64)) -> (BaseMatrix<4>, Sphere) {
+        let g1 = BaseMatrix::identity().rotate_y(FRAC_PI_2);
+        let g2 = BaseMatrix::identity().scale(inner_scale.0, inner_scale.1, inner_scale.2);
+        let s = Sphere {
+            transform: BaseMatrix::identity().translate(5, 0, 0),
+            ..Default::default()
+        };
+
+        (g1 * g2, s)
+    }
+
+    #[test]
+    fn converting_a_point_from_world_to_object_space() {
+        let (groups, s) = nested_sphere((2., 2., 2.));
+        let i = Intersection::new(1, &s).within_group(groups);
+
+        let p = i.world_to_object(point(-2, 0, -10));
+
+        assert_eq!(point(0, 0, -1), p);
+    }
+
+    #[test]
+    fn converting_a_normal_from_object_to_world_space() {
+        let (groups, s) = nested_sphere((1., 2., 3.));
+        let i = Intersection::new(1, &s).within_group(groups);
+        let n = 3f64.sqrt() / 3.;
+
+        let normal = i.normal_to_world(vector(n, n, n));
+
+        assert_eq!(vector(2. / 7., 3. / 7., -6. / 7.), normal);
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_child_object() {
+        let (groups, s) = nested_sphere((1., 2., 3.));
+        let i = Intersection::new(1, &s).within_group(groups);
+        let n = 3f64.sqrt() / 3.;
+        let on_surface = i.object_to_world() * point(n, n, n);
+
+        let normal = s.normal_at_hit(on_surface, &i);
+
+        assert_eq!(vector(2. / 7., 3. / 7., -6. / 7.), normal);
+    }
+
+    #[test]
+    fn hits_on_nested_children_carry_every_group_transform() {
+        let mut inner = Group::new(BaseMatrix::identity().scale(1, 2, 3));
+        inner.add_child(Sphere {
+            transform: BaseMatrix::identity().translate(5, 0, 0),
+            ..Default::default()
+        });
+        let mut outer = Group::new(BaseMatrix::identity().rotate_y(FRAC_PI_2));
+        outer.add_child(inner);
+        // straight down onto the top of the sphere, which sits at (0, 0, -5) after both
+        // groups' transforms
+        let r = Ray::new(point(0, 10, -5), vector(0, -1, 0));
+
+        let xs = outer.intersect(r).expect("must have intersections");
+        let comps = prepare_computations(&xs[0], &r, &xs);
+
+        assert_eq!(
+            nested_sphere((1., 2., 3.)).0,
+            xs[0].group_transform.unwrap()
+        );
+        assert_eq!(point(0, 2, -5), comps.point);
+        assert_eq!(vector(0, 1, 0), comps.normalv);
+    }
+}
@@ -1,13 +1,20 @@
-use apolaki_ray::Ray;
 use std::ops::Index;
+use std::ptr;
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct Intersections<O: Intersect> {
-    intersections: Vec<Intersection<O>>,
+use apolaki_matrix::{BaseMatrix, Invert};
+use apolaki_tuple::Tuple;
+
+use crate::shape::transform_normal;
+use crate::Shape;
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Intersections<'a> {
+    intersections: Vec<Intersection<'a>>,
 }
 
-impl<O: Intersect> Intersections<O> {
-    pub fn from(intersections: Vec<Intersection<O>>) -> Self {
+impl<'a> Intersections<'a> {
+    pub fn from(mut intersections: Vec<Intersection<'a>>) -> Self {
+        intersections.sort_by(|i1, i2| i1.t.total_cmp(&i2.t));
         Self { intersections }
     }
 
@@ -19,7 +26,11 @@ impl<O: Intersect> Intersections<O> {
         self.intersections.is_empty()
     }
 
-    pub fn hit(&self) -> Option<&Intersection<O>> {
+    pub fn iter(&self) -> impl Iterator<Item = &Intersection<'a>> {
+        self.intersections.iter()
+    }
+
+    pub fn hit(&self) -> Option<&Intersection<'a>> {
         self.intersections
             .iter()
             .filter(|i| i.t > 0.)
@@ -27,29 +38,90 @@ impl<O: Intersect> Intersections<O> {
     }
 }
 
-impl<O: Intersect> Index<usize> for Intersections<O> {
-    type Output = Intersection<O>;
+impl<'a> Index<usize> for Intersections<'a> {
+    type Output = Intersection<'a>;
 
     fn index(&self, index: usize) -> &Self::Output {
         self.intersections.index(index)
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Intersection<O: Intersect> {
+impl<'a> IntoIterator for Intersections<'a> {
+    type Item = Intersection<'a>;
+    type IntoIter = std::vec::IntoIter<Intersection<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intersections.into_iter()
+    }
+}
+
+impl<'a> FromIterator<Intersection<'a>> for Intersections<'a> {
+    fn from_iter<I: IntoIterator<Item = Intersection<'a>>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Intersection<'a> {
     pub t: f64,
-    pub obj: O,
+    pub obj: &'a dyn Shape,
+    // where on the surface the ray struck, for shapes that report it, like triangles
+    pub uv: Option<(f64, f64)>,
+    // the combined transform of every group `obj` sits in, outermost first
+    pub group_transform: Option<BaseMatrix<4>>,
 }
 
-impl<O: Intersect> Intersection<O> {
-    pub fn new<T: Into<f64>>(t: T, obj: O) -> Self {
-        Self { t: t.into(), obj }
+impl<'a> Intersection<'a> {
+    pub fn new<T: Into<f64>>(t: T, obj: &'a dyn Shape) -> Self {
+        Self {
+            t: t.into(),
+            obj,
+            uv: None,
+            group_transform: None,
+        }
+    }
+
+    pub fn with_uv<T: Into<f64>>(t: T, obj: &'a dyn Shape, u: f64, v: f64) -> Self {
+        Self {
+            t: t.into(),
+            obj,
+            uv: Some((u, v)),
+            group_transform: None,
+        }
+    }
+
+    // called by a group on its children's intersections as they pass up through it
+    pub fn within_group(mut self, transform: BaseMatrix<4>) -> Self {
+        self.group_transform = Some(match self.group_transform {
+            Some(inner) => transform * inner,
+            None => transform,
+        });
+        self
+    }
+
+    // everything between the object's space and world space: its own transform, then
+    // those of the groups around it
+    pub fn object_to_world(&self) -> BaseMatrix<4> {
+        match self.group_transform {
+            Some(groups) => groups * self.obj.transform(),
+            None => self.obj.transform(),
+        }
+    }
+
+    pub fn world_to_object(&self, point: Tuple) -> Tuple {
+        self.object_to_world().invert() * point
+    }
+
+    pub fn normal_to_world(&self, normal: Tuple) -> Tuple {
+        transform_normal(self.object_to_world().invert(), normal)
     }
 }
 
-pub trait Intersect {
-    type O: Intersect;
-    fn intersect(&self, ray: Ray) -> Option<Intersections<Self::O>>;
+impl PartialEq for Intersection<'_> {
+    // objects are compared by identity, not by value
+    fn eq(&self, other: &Self) -> bool {
+        self.t == other.t && ptr::addr_eq(self.obj, other.obj) && self.uv == other.uv
+    }
 }
 
 #[cfg(test)]
@@ -61,17 +133,27 @@ mod intersections_test {
     fn an_intersection_encapsulates_t_and_object() {
         let s = Sphere::default();
 
-        let i = Intersection::new(3.5, s);
+        let i = Intersection::new(3.5, &s);
 
         assert_eq!(i.t, 3.5);
-        assert_eq!(i.obj, s);
+        assert!(ptr::addr_eq(i.obj, &s));
+        assert_eq!(None, i.uv);
+    }
+
+    #[test]
+    fn an_intersection_can_encapsulate_u_and_v() {
+        let s = Sphere::default();
+
+        let i = Intersection::with_uv(3.5, &s, 0.2, 0.4);
+
+        assert_eq!(Some((0.2, 0.4)), i.uv);
     }
 
     #[test]
     fn aggregating_intersections() {
         let s = Sphere::default();
-        let i1 = Intersection::new(1, s);
-        let i2 = Intersection::new(2, s);
+        let i1 = Intersection::new(1, &s);
+        let i2 = Intersection::new(2, &s);
 
         let xs = Intersections::from(vec![i1, i2]);
 
@@ -80,11 +162,25 @@ mod intersections_test {
         assert_eq!(xs[1].t, 2.0);
     }
 
+    #[test]
+    fn intersections_are_sorted_by_t() {
+        let s = Sphere::default();
+        let i1 = Intersection::new(5, &s);
+        let i2 = Intersection::new(-3, &s);
+        let i3 = Intersection::new(2, &s);
+
+        let xs = Intersections::from(vec![i1, i2, i3]);
+
+        assert_eq!(xs[0], i2);
+        assert_eq!(xs[1], i3);
+        assert_eq!(xs[2], i1);
+    }
+
     #[test]
     fn the_hit_when_all_intersections_have_positive_t() {
         let s = Sphere::default();
-        let i1 = Intersection::new(1, s);
-        let i2 = Intersection::new(2, s);
+        let i1 = Intersection::new(1, &s);
+        let i2 = Intersection::new(2, &s);
         let xs = Intersections::from(vec![i2, i1]);
 
         let i = xs.hit();
@@ -95,8 +191,8 @@ mod intersections_test {
     #[test]
     fn the_hit_when_some_intersections_have_negative_t() {
         let s = Sphere::default();
-        let i1 = Intersection::new(-1, s);
-        let i2 = Intersection::new(1, s);
+        let i1 = Intersection::new(-1, &s);
+        let i2 = Intersection::new(1, &s);
         let xs = Intersections::from(vec![i2, i1]);
 
         let i = xs.hit();
@@ -107,8 +203,8 @@ mod intersections_test {
     #[test]
     fn the_hit_when_all_intersections_have_negative_t() {
         let s = Sphere::default();
-        let i1 = Intersection::new(-2, s);
-        let i2 = Intersection::new(-1, s);
+        let i1 = Intersection::new(-2, &s);
+        let i2 = Intersection::new(-1, &s);
         let xs = Intersections::from(vec![i2, i1]);
 
         let i = xs.hit();
@@ -119,10 +215,10 @@ mod intersections_test {
     #[test]
     fn the_hit_is_always_the_lowest_non_negative_intersection() {
         let s = Sphere::default();
-        let i1 = Intersection::new(5, s);
-        let i2 = Intersection::new(7, s);
-        let i3 = Intersection::new(-3, s);
-        let i4 = Intersection::new(2, s);
+        let i1 = Intersection::new(5, &s);
+        let i2 = Intersection::new(7, &s);
+        let i3 = Intersection::new(-3, &s);
+        let i4 = Intersection::new(2, &s);
         let xs = Intersections::from(vec![i1, i2, i3, i4]);
 
         let i = xs.hit();
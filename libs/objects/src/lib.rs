@@ -1,7 +1,33 @@
 extern crate core;
 
+pub use bounds::*;
+pub use bvh::*;
+pub use computations::*;
+pub use cone::*;
+pub use csg::*;
+pub use cube::*;
+pub use cylinder::*;
+pub use group::*;
 pub use intersect::*;
+pub use obj::*;
+pub use plane::*;
+pub use shape::*;
+pub use smooth_triangle::*;
 pub use sphere::*;
+pub use triangle::*;
 
+mod bounds;
+mod bvh;
+mod computations;
+mod cone;
+mod csg;
+mod cube;
+mod cylinder;
+mod group;
 mod intersect;
+mod obj;
+mod plane;
+mod shape;
+mod smooth_triangle;
 mod sphere;
+mod triangle;
@@ -0,0 +1,640 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::{point, vector, Tuple};
+
+use crate::bounds::Bounds;
+use crate::group::Group;
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
+use crate::smooth_triangle::SmoothTriangle;
+use crate::triangle::Triangle;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObjError {
+    // a statement's arguments couldn't be read
+    Malformed { line: usize },
+    // a face refers to a vertex, texture coordinate or normal that doesn't exist
+    BadIndex { line: usize, index: i64 },
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjError::Malformed { line } => write!(f, "line {line}: malformed statement"),
+            ObjError::BadIndex { line, index } => {
+                write!(f, "line {line}: index {index} is out of range")
+            }
+        }
+    }
+}
+
+impl Error for ObjError {}
+
+// a face of a mesh: smooth when the file gives a normal for every vertex, flat otherwise
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObjTriangle {
+    Flat(Triangle),
+    Smooth(SmoothTriangle),
+}
+
+impl ObjTriangle {
+    fn shape(&self) -> &dyn Shape {
+        match self {
+            ObjTriangle::Flat(t) => t,
+            ObjTriangle::Smooth(t) => t,
+        }
+    }
+}
+
+impl Shape for ObjTriangle {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.shape().transform()
+    }
+
+    fn material(&self) -> &Material {
+        match self {
+            ObjTriangle::Flat(t) => &t.material,
+            ObjTriangle::Smooth(t) => &t.material,
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.shape().bounds()
+    }
+
+    // hits on a mesh face name the inner triangle, not this wrapper
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.shape().includes(other)
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        self.shape().local_intersect(ray)
+    }
+
+    fn local_normal_at(&self, at: Tuple) -> Tuple {
+        self.shape().local_normal_at(at)
+    }
+
+    fn local_normal_at_hit(&self, at: Tuple, hit: &Intersection) -> Tuple {
+        self.shape().local_normal_at_hit(at, hit)
+    }
+}
+
+// Everything read from an OBJ file. Indices in the file start at 1, but these lists start
+// at 0.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjFile {
+    pub vertices: Vec<Tuple>,
+    pub normals: Vec<Tuple>,
+    pub texture_coords: Vec<(f64, f64)>,
+    // faces that come before any `g` statement
+    pub default_group: Vec<ObjTriangle>,
+    // named groups, in the order they first appear
+    pub groups: Vec<(String, Vec<ObjTriangle>)>,
+    // non-blank lines that weren't understood, comments included
+    pub ignored: usize,
+    // triangles left out because they have no area, and so no normal
+    pub degenerate: usize,
+}
+
+impl ObjFile {
+    pub fn group(&self, name: &str) -> Option<&[ObjTriangle]> {
+        self.groups
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, triangles)| triangles.as_slice())
+    }
+
+    // the list that faces are currently being added to
+    fn current_group(&mut self, group: Option<usize>) -> &mut Vec<ObjTriangle> {
+        match group {
+            Some(i) => &mut self.groups[i].1,
+            None => &mut self.default_group,
+        }
+    }
+}
+
+// a group holding the default group's faces, plus a child group for each named group
+impl From<ObjFile> for Group {
+    fn from(obj: ObjFile) -> Self {
+        let mut group = Group::default();
+        for triangle in obj.default_group {
+            group.add_child(triangle);
+        }
+        for (_, triangles) in obj.groups {
+            let mut named = Group::default();
+            for triangle in triangles {
+                named.add_child(triangle);
+            }
+            group.add_child(named);
+        }
+
+        group
+    }
+}
+
+// the indices of one corner of a face, already zero-based and checked
+struct FaceVertex {
+    vertex: usize,
+    normal: Option<usize>,
+}
+
+pub fn parse_obj(text: &str) -> Result<ObjFile, ObjError> {
+    let mut obj = ObjFile::default();
+    let mut group = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let malformed = ObjError::Malformed { line: line_number };
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            None => {}
+            Some("v") => {
+                // anything after the position, like a weight or a vertex color, is unused
+                let [x, y, z] = numbers(words.take(3), &malformed)?;
+                obj.vertices.push(point(x, y, z));
+            }
+            Some("vn") => {
+                let [x, y, z] = numbers(words, &malformed)?;
+                obj.normals.push(vector(x, y, z));
+            }
+            Some("vt") => {
+                let values = words
+                    .map(|word| number(word, &malformed))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // v defaults to 0, and an optional third coordinate is unused
+                let uv = match values[..] {
+                    [u] => (u, 0.),
+                    [u, v] | [u, v, _] => (u, v),
+                    _ => return Err(malformed),
+                };
+                obj.texture_coords.push(uv);
+            }
+            Some("f") => {
+                let corners = words
+                    .map(|word| face_vertex(word, &obj, line_number))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if corners.len() < 3 {
+                    return Err(malformed);
+                }
+
+                let triangles = fan_triangulation(&corners, &obj);
+                obj.degenerate += corners.len() - 2 - triangles.len();
+                obj.current_group(group).extend(triangles);
+            }
+            Some("g") => {
+                let name = words.collect::<Vec<_>>().join(" ");
+                group = Some(match obj.groups.iter().position(|(n, _)| *n == name) {
+                    Some(existing) => existing,
+                    None => {
+                        obj.groups.push((name, Vec::new()));
+                        obj.groups.len() - 1
+                    }
+                });
+            }
+            Some(_) => obj.ignored += 1,
+        }
+    }
+
+    Ok(obj)
+}
+
+// exactly `N` numbers
+fn numbers<'a, const N: usize>(
+    words: impl Iterator<Item = &'a str>,
+    malformed: &ObjError,
+) -> Result<[f64; N], ObjError> {
+    let values = words
+        .map(|word| number(word, malformed))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    values.try_into().map_err(|_| malformed.clone())
+}
+
+// a finite number; `nan` and `inf` parse, but can't be placed in a scene
+fn number(word: &str, malformed: &ObjError) -> Result<f64, ObjError> {
+    match word.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err(malformed.clone()),
+    }
+}
+
+// one of `v`, `v/vt`, `v//vn` or `v/vt/vn`
+fn face_vertex(word: &str, obj: &ObjFile, line: usize) -> Result<FaceVertex, ObjError> {
+    let mut parts = word.split('/');
+    let vertex = parts.next().unwrap_or_default();
+    let texture = parts.next().filter(|part| !part.is_empty());
+    let normal = parts.next().filter(|part| !part.is_empty());
+    if parts.next().is_some() {
+        return Err(ObjError::Malformed { line });
+    }
+
+    let vertex = resolve_index(vertex, obj.vertices.len(), line)?;
+    // texture coordinates are checked, but triangles don't use them
+    texture
+        .map(|t| resolve_index(t, obj.texture_coords.len(), line))
+        .transpose()?;
+    let normal = normal
+        .map(|n| resolve_index(n, obj.normals.len(), line))
+        .transpose()?;
+
+    Ok(FaceVertex { vertex, normal })
+}
+
+// OBJ indices count from 1, or back from the end of the list when negative
+fn resolve_index(word: &str, len: usize, line: usize) -> Result<usize, ObjError> {
+    let index: i64 = word.parse().map_err(|_| ObjError::Malformed { line })?;
+    let bad_index = ObjError::BadIndex { line, index };
+
+    let resolved = if index > 0 {
+        index - 1
+    } else if index < 0 {
+        len as i64 + index
+    } else {
+        return Err(bad_index);
+    };
+
+    if (0..len as i64).contains(&resolved) {
+        Ok(resolved as usize)
+    } else {
+        Err(bad_index)
+    }
+}
+
+// splits a convex polygon into triangles that all share its first vertex, leaving out any
+// with collinear or repeated corners
+fn fan_triangulation(corners: &[FaceVertex], obj: &ObjFile) -> Vec<ObjTriangle> {
+    let first = &corners[0];
+
+    corners[1..]
+        .windows(2)
+        .filter_map(|pair| {
+            let (b, c) = (&pair[0], &pair[1]);
+            let (p1, p2, p3) = (
+                obj.vertices[first.vertex],
+                obj.vertices[b.vertex],
+                obj.vertices[c.vertex],
+            );
+            if (p2 - p1).cross(p3 - p1).length() == 0. {
+                return None;
+            }
+
+            Some(match (first.normal, b.normal, c.normal) {
+                (Some(n1), Some(n2), Some(n3)) => ObjTriangle::Smooth(SmoothTriangle::new(
+                    p1,
+                    p2,
+                    p3,
+                    obj.normals[n1],
+                    obj.normals[n2],
+                    obj.normals[n3],
+                )),
+                _ => ObjTriangle::Flat(Triangle::new(p1, p2, p3)),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod obj_tests {
+    use super::*;
+
+    fn flat(t: &ObjTriangle) -> &Triangle {
+        match t {
+            ObjTriangle::Flat(t) => t,
+            ObjTriangle::Smooth(_) => panic!("expected a flat triangle"),
+        }
+    }
+
+    fn smooth(t: &ObjTriangle) -> &SmoothTriangle {
+        match t {
+            ObjTriangle::Smooth(t) => t,
+            ObjTriangle::Flat(_) => panic!("expected a smooth triangle"),
+        }
+    }
+
+    #[test]
+    fn ignoring_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright
+who traveled much faster than light.
+She set out one day
+
+in a relative way,
+and came back the previous night.";
+
+        let obj = parse_obj(gibberish).expect("must parse");
+
+        assert_eq!(5, obj.ignored);
+    }
+
+    #[test]
+    fn vertex_records() {
+        let file = "v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0";
+
+        let obj = parse_obj(file).expect("must parse");
+
+        assert_eq!(point(-1, 1, 0), obj.vertices[0]);
+        assert_eq!(point(-1, 0.5, 0), obj.vertices[1]);
+        assert_eq!(point(1, 0, 0), obj.vertices[2]);
+        assert_eq!(point(1, 1, 0), obj.vertices[3]);
+    }
+
+    #[test]
+    fn parsing_triangle_faces() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 3
+f 1 3 4";
+
+        let obj = parse_obj(file).expect("must parse");
+        let t1 = flat(&obj.default_group[0]);
+        let t2 = flat(&obj.default_group[1]);
+
+        assert_eq!(obj.vertices[0], t1.p1);
+        assert_eq!(obj.vertices[1], t1.p2);
+        assert_eq!(obj.vertices[2], t1.p3);
+        assert_eq!(obj.vertices[0], t2.p1);
+        assert_eq!(obj.vertices[2], t2.p2);
+        assert_eq!(obj.vertices[3], t2.p3);
+    }
+
+    #[test]
+    fn triangulating_polygons() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+v 0 2 0
+
+f 1 2 3 4 5";
+
+        let obj = parse_obj(file).expect("must parse");
+        let triangles: Vec<_> = obj.default_group.iter().map(flat).collect();
+
+        assert_eq!(3, triangles.len());
+        for (t, (b, c)) in triangles.iter().zip([(1, 2), (2, 3), (3, 4)]) {
+            assert_eq!(obj.vertices[0], t.p1);
+            assert_eq!(obj.vertices[b], t.p2);
+            assert_eq!(obj.vertices[c], t.p3);
+        }
+    }
+
+    #[test]
+    fn triangles_in_groups() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+
+        let obj = parse_obj(file).expect("must parse");
+        let t1 = flat(&obj.group("FirstGroup").expect("must have a first group")[0]);
+        let t2 = flat(&obj.group("SecondGroup").expect("must have a second group")[0]);
+
+        assert!(obj.default_group.is_empty());
+        assert_eq!(obj.vertices[0], t1.p1);
+        assert_eq!(obj.vertices[2], t1.p3);
+        assert_eq!(obj.vertices[0], t2.p1);
+        assert_eq!(obj.vertices[3], t2.p3);
+    }
+
+    #[test]
+    fn reopening_a_group_adds_to_it() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+g A
+f 1 2 3
+g B
+f 1 2 3
+g A
+f 3 2 1";
+
+        let obj = parse_obj(file).expect("must parse");
+
+        assert_eq!(2, obj.groups.len());
+        assert_eq!(2, obj.group("A").map_or(0, <[_]>::len));
+    }
+
+    #[test]
+    fn converting_an_obj_file_to_a_group() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+f 1 2 3
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+        let obj = parse_obj(file).expect("must parse");
+
+        let g = Group::from(obj);
+
+        // the loose face, then one group for each name
+        assert_eq!(3, g.children().len());
+        assert!(g
+            .intersect(Ray::new(point(0.5, 0.9, -2), vector(0, 0, 1)))
+            .is_some());
+    }
+
+    #[test]
+    fn vertex_normal_and_texture_records() {
+        let file = "vn 0 0 1
+vn 0.707 0 -0.707
+vn 1 2 3
+vt 0.5 0.25
+vt 0 1 0";
+
+        let obj = parse_obj(file).expect("must parse");
+
+        assert_eq!(vector(0, 0, 1), obj.normals[0]);
+        assert_eq!(vector(0.707, 0, -0.707), obj.normals[1]);
+        assert_eq!(vector(1, 2, 3), obj.normals[2]);
+        assert_eq!(vec![(0.5, 0.25), (0., 1.)], obj.texture_coords);
+    }
+
+    #[test]
+    fn faces_with_normals() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+
+vn -1 0 0
+vn 1 0 0
+vn 0 1 0
+
+vt 0 0
+
+f 1//3 2//1 3//2
+f 1/1/3 2/1/1 3/1/2";
+
+        let obj = parse_obj(file).expect("must parse");
+        let t1 = smooth(&obj.default_group[0]);
+        let t2 = smooth(&obj.default_group[1]);
+
+        assert_eq!(obj.vertices[0], t1.p1);
+        assert_eq!(obj.vertices[1], t1.p2);
+        assert_eq!(obj.vertices[2], t1.p3);
+        assert_eq!(obj.normals[2], t1.n1);
+        assert_eq!(obj.normals[0], t1.n2);
+        assert_eq!(obj.normals[1], t1.n3);
+        assert_eq!(t1, t2);
+    }
+
+    #[test]
+    fn faces_missing_a_normal_are_flat() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+vn 0 0 -1
+f 1//1 2 3//1";
+
+        let obj = parse_obj(file).expect("must parse");
+
+        flat(&obj.default_group[0]);
+    }
+
+    #[test]
+    fn negative_indices_count_back_from_the_end() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+f -3 -2 -1";
+
+        let obj = parse_obj(file).expect("must parse");
+        let t = flat(&obj.default_group[0]);
+
+        assert_eq!(obj.vertices[0], t.p1);
+        assert_eq!(obj.vertices[2], t.p3);
+    }
+
+    #[test]
+    fn faces_referring_to_missing_vertices_are_errors() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+f 1 2 4";
+
+        assert_eq!(
+            Err(ObjError::BadIndex { line: 4, index: 4 }),
+            parse_obj(file)
+        );
+        assert_eq!(
+            Err(ObjError::BadIndex { line: 1, index: 0 }),
+            parse_obj("f 0 1 2")
+        );
+    }
+
+    #[test]
+    fn faces_referring_to_missing_normals_are_errors() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+vn 0 0 1
+f 1//1 2//2 3//1";
+
+        assert_eq!(
+            Err(ObjError::BadIndex { line: 5, index: 2 }),
+            parse_obj(file)
+        );
+    }
+
+    #[test]
+    fn extra_vertex_values_are_ignored() {
+        let file = "v -1 1 0 0.5
+v 1 2 3 1 0 0";
+        let obj = parse_obj(file).expect("must parse");
+
+        assert_eq!(vec![point(-1, 1, 0), point(1, 2, 3)], obj.vertices);
+    }
+
+    #[test]
+    fn texture_coordinates_take_one_to_three_values() {
+        let file = "vt 0.25
+vt 0.5 0.75
+vt 0.1 0.2 0.3";
+        let obj = parse_obj(file).expect("must parse");
+
+        assert_eq!(
+            vec![(0.25, 0.), (0.5, 0.75), (0.1, 0.2)],
+            obj.texture_coords
+        );
+        assert_eq!(Err(ObjError::Malformed { line: 1 }), parse_obj("vt"));
+        assert_eq!(
+            Err(ObjError::Malformed { line: 1 }),
+            parse_obj("vt 1 2 3 4")
+        );
+    }
+
+    #[test]
+    fn faces_without_area_are_left_out() {
+        let file = "v 0 0 0
+v 1 0 0
+v 2 0 0
+v 0 1 0
+f 1 2 3
+f 1 1 4
+f 1 2 3 4";
+
+        let obj = parse_obj(file).expect("must parse");
+
+        // only the second half of the quad has any area
+        assert_eq!(3, obj.degenerate);
+        assert_eq!(1, obj.default_group.len());
+        assert_eq!(vector(0, 0, -1), flat(&obj.default_group[0]).normal);
+    }
+
+    #[test]
+    fn malformed_statements_are_errors() {
+        assert_eq!(
+            Err(ObjError::Malformed { line: 2 }),
+            parse_obj("v 0 1 0\nv 1 x 0")
+        );
+        assert_eq!(Err(ObjError::Malformed { line: 1 }), parse_obj("v 0 1"));
+        assert_eq!(
+            Err(ObjError::Malformed { line: 1 }),
+            parse_obj("v nan inf 1")
+        );
+        assert_eq!(
+            Err(ObjError::Malformed { line: 1 }),
+            parse_obj("vn 0 -inf 0")
+        );
+        assert_eq!(Err(ObjError::Malformed { line: 1 }), parse_obj("vt NaN"));
+        assert_eq!(
+            Err(ObjError::Malformed { line: 3 }),
+            parse_obj("v 0 1 0\nv 1 0 0\nf 1 2")
+        );
+    }
+
+    #[test]
+    fn mesh_triangles_are_shapes() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+f 1 2 3";
+        let obj = parse_obj(file).expect("must parse");
+        let t = &obj.default_group[0];
+
+        let xs = t
+            .intersect(Ray::new(point(0, 0.5, -2), vector(0, 0, 1)))
+            .expect("must have intersections");
+
+        assert_eq!(2., xs[0].t);
+        assert_eq!(vector(0, 0, -1), t.normal_at(point(0, 0.5, 0)));
+    }
+}
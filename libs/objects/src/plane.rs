@@ -0,0 +1,110 @@
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::{point, vector, Tuple};
+
+use crate::bounds::Bounds;
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
+use crate::EPSILON;
+
+// the xz plane, infinite in both directions
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plane {
+    pub transform: BaseMatrix<4>,
+    pub material: Material,
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self {
+            transform: BaseMatrix::identity(),
+            material: Material::default(),
+        }
+    }
+}
+
+impl Shape for Plane {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            point(f64::NEG_INFINITY, 0, f64::NEG_INFINITY),
+            point(f64::INFINITY, 0, f64::INFINITY),
+        )
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        // parallel (or coplanar) rays never see the plane
+        if ray.dir.y().abs() < EPSILON {
+            return None;
+        }
+
+        let t = -ray.origin.y() / ray.dir.y();
+        Some(Intersections::from(vec![Intersection::new(t, self)]))
+    }
+
+    fn local_normal_at(&self, _at: Tuple) -> Tuple {
+        vector(0, 1, 0)
+    }
+}
+
+#[cfg(test)]
+mod plane_tests {
+    use super::*;
+
+    #[test]
+    fn the_normal_of_a_plane_is_constant_everywhere() {
+        let p = Plane::default();
+
+        assert_eq!(vector(0, 1, 0), p.local_normal_at(point(0, 0, 0)));
+        assert_eq!(vector(0, 1, 0), p.local_normal_at(point(10, 0, -10)));
+        assert_eq!(vector(0, 1, 0), p.local_normal_at(point(-5, 0, 150)));
+    }
+
+    #[test]
+    fn intersect_with_a_ray_parallel_to_the_plane() {
+        let p = Plane::default();
+        let r = Ray::new(point(0, 10, 0), vector(0, 0, 1));
+
+        assert_eq!(None, p.local_intersect(r));
+    }
+
+    #[test]
+    fn intersect_with_a_coplanar_ray() {
+        let p = Plane::default();
+        let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
+
+        assert_eq!(None, p.local_intersect(r));
+    }
+
+    #[test]
+    fn a_ray_intersecting_a_plane_from_above() {
+        let p = Plane::default();
+        let r = Ray::new(point(0, 1, 0), vector(0, -1, 0));
+
+        let xs = p.local_intersect(r).expect("must have intersections");
+
+        assert_eq!(1, xs.len());
+        assert_eq!(1., xs[0].t);
+        assert!(std::ptr::addr_eq(xs[0].obj, &p));
+    }
+
+    #[test]
+    fn a_ray_intersecting_a_plane_from_below() {
+        let p = Plane::default();
+        let r = Ray::new(point(0, -1, 0), vector(0, 1, 0));
+
+        let xs = p.local_intersect(r).expect("must have intersections");
+
+        assert_eq!(1, xs.len());
+        assert_eq!(1., xs[0].t);
+        assert!(std::ptr::addr_eq(xs[0].obj, &p));
+    }
+}
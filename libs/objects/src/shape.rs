@@ -1 +1,174 @@
-pub trait Shape {}
+use std::fmt::Debug;
+use std::ptr;
+
+use apolaki_material::Material;
+use apolaki_matrix::{BaseMatrix, Invert};
+use apolaki_ray::Ray;
+use apolaki_transform::Transform;
+use apolaki_tuple::Tuple;
+
+use crate::{Bounds, Intersection, Intersections};
+
+// Shapes only describe themselves in object space; the default methods move rays and
+// normals between world and object space using the shape's transform. Shapes are shared
+// across render threads, so they must be `Send + Sync`.
+pub trait Shape: Debug + Send + Sync {
+    fn transform(&self) -> BaseMatrix<4>;
+    fn material(&self) -> &Material;
+
+    // intersections with a ray that is already in object space
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>>;
+
+    // the normal at a point that is already in object space
+    fn local_normal_at(&self, at: Tuple) -> Tuple;
+
+    // like `local_normal_at`, for shapes that use where `hit` struck them, like smooth
+    // triangles interpolating their vertex normals
+    fn local_normal_at_hit(&self, at: Tuple, _hit: &Intersection) -> Tuple {
+        self.local_normal_at(at)
+    }
+
+    // A box around the shape in object space. Shapes that don't give one are assumed to
+    // reach everywhere, so every ray is tested against them.
+    fn bounds(&self) -> Bounds {
+        Bounds::infinite()
+    }
+
+    // the box around the shape in the space of whatever holds it, after its transform
+    fn parent_space_bounds(&self) -> Bounds {
+        self.bounds().transform(self.transform())
+    }
+
+    // whether `other` is this shape, or one of the shapes it is built from
+    fn includes(&self, other: &dyn Shape) -> bool {
+        ptr::addr_eq(self, other)
+    }
+
+    fn intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        self.local_intersect(ray.transform(self.transform().invert()))
+    }
+
+    fn normal_at(&self, at: Tuple) -> Tuple {
+        if at.is_vec() {
+            panic!("parameter `at` must be a point")
+        }
+
+        let inverse = self.transform().invert();
+        transform_normal(inverse, self.local_normal_at(inverse * at))
+    }
+
+    // like `normal_at`, but also going through every group `hit` found this shape in
+    fn normal_at_hit(&self, at: Tuple, hit: &Intersection) -> Tuple {
+        if at.is_vec() {
+            panic!("parameter `at` must be a point")
+        }
+
+        let inverse = hit.object_to_world().invert();
+        transform_normal(inverse, self.local_normal_at_hit(inverse * at, hit))
+    }
+}
+
+// Normals go back by the inverse-transpose of the transform, so they stay perpendicular
+// to the surface.
+pub(crate) fn transform_normal(inverse: BaseMatrix<4>, local_normal: Tuple) -> Tuple {
+    let mut world_normal = inverse.transpose() * local_normal;
+    world_normal.set_w(0.);
+
+    world_normal.normalize()
+}
+
+#[cfg(test)]
+mod shape_tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI, SQRT_2};
+    use std::sync::Mutex;
+
+    use apolaki_tuple::{point, vector};
+
+    use super::*;
+
+    // a shape that records the object-space ray it was asked to intersect
+    #[derive(Debug, Default)]
+    struct TestShape {
+        transform: BaseMatrix<4>,
+        material: Material,
+        saved_ray: Mutex<Option<Ray>>,
+    }
+
+    impl Shape for TestShape {
+        fn transform(&self) -> BaseMatrix<4> {
+            self.transform
+        }
+
+        fn material(&self) -> &Material {
+            &self.material
+        }
+
+        fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+            *self.saved_ray.lock().unwrap() = Some(ray);
+            None
+        }
+
+        fn local_normal_at(&self, at: Tuple) -> Tuple {
+            vector(at.x(), at.y(), at.z())
+        }
+    }
+
+    fn test_shape(transform: BaseMatrix<4>) -> TestShape {
+        TestShape {
+            transform,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn intersecting_a_scaled_shape_with_a_ray() {
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let s = test_shape(BaseMatrix::identity().scale(2, 2, 2));
+
+        s.intersect(r);
+
+        let saved = s
+            .saved_ray
+            .lock()
+            .unwrap()
+            .expect("must have been intersected");
+        assert_eq!(point(0, 0, -2.5), saved.origin);
+        assert_eq!(vector(0, 0, 0.5), saved.dir);
+    }
+
+    #[test]
+    fn intersecting_a_translated_shape_with_a_ray() {
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let s = test_shape(BaseMatrix::identity().translate(5, 0, 0));
+
+        s.intersect(r);
+
+        let saved = s
+            .saved_ray
+            .lock()
+            .unwrap()
+            .expect("must have been intersected");
+        assert_eq!(point(-5, 0, -5), saved.origin);
+        assert_eq!(vector(0, 0, 1), saved.dir);
+    }
+
+    #[test]
+    fn computing_the_normal_on_a_translated_shape() {
+        let s = test_shape(BaseMatrix::identity().translate(0, 1, 0));
+
+        let n = s.normal_at(point(0, 1. + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+
+        assert_eq!(vector(0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2), n);
+    }
+
+    #[test]
+    fn computing_the_normal_on_a_transformed_shape() {
+        let s = test_shape(
+            BaseMatrix::identity().scale(1, 0.5, 1) * BaseMatrix::identity().rotate_z(PI / 5.),
+        );
+
+        let n = s.normal_at(point(0, SQRT_2 / 2., -SQRT_2 / 2.));
+
+        assert_eq!(vector(0, 0.97014, -0.24254), n);
+    }
+}
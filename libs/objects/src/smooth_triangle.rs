@@ -0,0 +1,175 @@
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::Tuple;
+
+use crate::bounds::Bounds;
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
+use crate::triangle::intersect_triangle;
+
+// a triangle whose normal is interpolated between normals given at each vertex
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmoothTriangle {
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub n1: Tuple,
+    pub n2: Tuple,
+    pub n3: Tuple,
+    // edges from `p1`, computed once up front
+    pub e1: Tuple,
+    pub e2: Tuple,
+    pub transform: BaseMatrix<4>,
+    pub material: Material,
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple, n1: Tuple, n2: Tuple, n3: Tuple) -> Self {
+        Self {
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+            transform: BaseMatrix::identity(),
+            material: Material::default(),
+        }
+    }
+
+    // the barycentric `u` and `v` of a point on the triangle
+    fn barycentric(&self, at: Tuple) -> (f64, f64) {
+        let p = at - self.p1;
+        let (d11, d12, d22) = (
+            self.e1.dot(self.e1),
+            self.e1.dot(self.e2),
+            self.e2.dot(self.e2),
+        );
+        let (dp1, dp2) = (p.dot(self.e1), p.dot(self.e2));
+        let denom = d11 * d22 - d12 * d12;
+
+        (
+            (d22 * dp1 - d12 * dp2) / denom,
+            (d11 * dp2 - d12 * dp1) / denom,
+        )
+    }
+
+    fn interpolate_normal(&self, (u, v): (f64, f64)) -> Tuple {
+        self.n2 * u + self.n3 * v + self.n1 * (1. - u - v)
+    }
+}
+
+impl Shape for SmoothTriangle {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Bounds {
+        let mut bounds = Bounds::empty();
+        for p in [self.p1, self.p2, self.p3] {
+            bounds.add_point(p);
+        }
+        bounds
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let (t, u, v) = intersect_triangle(self.p1, self.e1, self.e2, ray)?;
+
+        Some(Intersections::from(vec![Intersection::with_uv(
+            t, self, u, v,
+        )]))
+    }
+
+    // without a hit to say where it landed, u and v are worked out from the point
+    fn local_normal_at(&self, at: Tuple) -> Tuple {
+        self.interpolate_normal(self.barycentric(at))
+    }
+
+    fn local_normal_at_hit(&self, at: Tuple, hit: &Intersection) -> Tuple {
+        match hit.uv {
+            Some(uv) => self.interpolate_normal(uv),
+            None => self.local_normal_at(at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod smooth_triangle_tests {
+    use apolaki_tuple::{point, vector};
+
+    use super::*;
+    use crate::prepare_computations;
+
+    fn test_triangle() -> SmoothTriangle {
+        SmoothTriangle::new(
+            point(0, 1, 0),
+            point(-1, 0, 0),
+            point(1, 0, 0),
+            vector(0, 1, 0),
+            vector(-1, 0, 0),
+            vector(1, 0, 0),
+        )
+    }
+
+    #[test]
+    fn constructing_a_smooth_triangle() {
+        let tri = test_triangle();
+
+        assert_eq!(point(0, 1, 0), tri.p1);
+        assert_eq!(point(-1, 0, 0), tri.p2);
+        assert_eq!(point(1, 0, 0), tri.p3);
+        assert_eq!(vector(0, 1, 0), tri.n1);
+        assert_eq!(vector(-1, 0, 0), tri.n2);
+        assert_eq!(vector(1, 0, 0), tri.n3);
+    }
+
+    #[test]
+    fn an_intersection_with_a_smooth_triangle_stores_u_and_v() {
+        let tri = test_triangle();
+        let r = Ray::new(point(-0.2, 0.3, -2), vector(0, 0, 1));
+
+        let xs = tri.local_intersect(r).expect("must have intersections");
+        let (u, v) = xs[0].uv.expect("must report u and v");
+
+        assert!((0.45 - u).abs() < 0.00001);
+        assert!((0.25 - v).abs() < 0.00001);
+    }
+
+    #[test]
+    fn a_smooth_triangle_uses_u_and_v_to_interpolate_the_normal() {
+        let tri = test_triangle();
+        let i = Intersection::with_uv(1, &tri, 0.45, 0.25);
+
+        let n = tri.normal_at_hit(point(0, 0, 0), &i);
+
+        assert_eq!(vector(-0.5547, 0.83205, 0), n);
+    }
+
+    #[test]
+    fn without_a_hit_the_normal_is_interpolated_from_the_point() {
+        let tri = test_triangle();
+
+        let n = tri.normal_at(point(-0.2, 0.3, 0));
+
+        assert_eq!(vector(-0.5547, 0.83205, 0), n);
+    }
+
+    #[test]
+    fn preparing_the_normal_on_a_smooth_triangle() {
+        let tri = test_triangle();
+        let i = Intersection::with_uv(1, &tri, 0.45, 0.25);
+        let r = Ray::new(point(-0.2, 0.3, -2), vector(0, 0, 1));
+        let xs = Intersections::from(vec![i]);
+
+        let comps = prepare_computations(&i, &r, &xs);
+
+        assert_eq!(vector(-0.5547, 0.83205, 0), comps.normalv);
+    }
+}
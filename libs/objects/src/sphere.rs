@@ -1,12 +1,13 @@
 use apolaki_material::Material;
-use apolaki_matrix::{BaseMatrix, Invert};
+use apolaki_matrix::BaseMatrix;
 use apolaki_ray::Ray;
-use apolaki_transform::Transform;
 use apolaki_tuple::{point, Tuple};
 
-use crate::intersect::{Intersect, Intersection, Intersections};
+use crate::bounds::Bounds;
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sphere {
     pub radius: f64,
     pub transform: BaseMatrix<4>,
@@ -22,24 +23,22 @@ impl Sphere {
         }
     }
 
-    pub fn normal_at(&self, at: Tuple) -> Tuple {
-        if at.is_vec() {
-            panic!("parameter `at` must be a point")
-        }
-
-        let object_normal = {
-            let object_point = self.transform.invert() * at;
-            object_point - point(0, 0, 0)
-        };
-
-        let mut world_normal = self.transform.invert().transpose() * object_normal;
-        world_normal.set_w(0.);
-
-        world_normal.normalize()
+    // composes `transform` with the current one, so it applies first; this was
+    // `Sphere::transform` before that name went to `Shape::transform`
+    pub fn set_transform(&mut self, transform: BaseMatrix<4>) {
+        self.transform = self.transform * transform;
     }
 
-    pub fn transform(&mut self, transform: BaseMatrix<4>) {
-        self.transform = self.transform * transform;
+    // a unit sphere of clear glass
+    pub fn glass() -> Self {
+        Self {
+            material: Material {
+                transparency: 1.0,
+                refractive_index: 1.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
     }
 }
 
@@ -53,13 +52,23 @@ impl Default for Sphere {
     }
 }
 
-impl Intersect for Sphere {
-    type O = Self;
-    fn intersect(&self, ray: Ray) -> Option<Intersections<Self::O>> {
-        let r = ray.transform(self.transform.invert());
-        let sphere_to_ray = r.origin - point(0, 0, 0);
-        let a = r.dir.dot(r.dir);
-        let b = 2. * r.dir.dot(sphere_to_ray);
+impl Shape for Sphere {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(point(-1, -1, -1), point(1, 1, 1))
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let sphere_to_ray = ray.origin - point(0, 0, 0);
+        let a = ray.dir.dot(ray.dir);
+        let b = 2. * ray.dir.dot(sphere_to_ray);
         let c = sphere_to_ray.dot(sphere_to_ray) - 1.;
 
         let discriminant = b.powi(2) - 4. * a * c;
@@ -68,11 +77,15 @@ impl Intersect for Sphere {
             None
         } else {
             Some(Intersections::from(Vec::from([
-                Intersection::new((-b - discriminant.sqrt()) / (2. * a), *self),
-                Intersection::new((-b + discriminant.sqrt()) / (2. * a), *self),
+                Intersection::new((-b - discriminant.sqrt()) / (2. * a), self),
+                Intersection::new((-b + discriminant.sqrt()) / (2. * a), self),
             ])))
         }
     }
+
+    fn local_normal_at(&self, at: Tuple) -> Tuple {
+        at - point(0, 0, 0)
+    }
 }
 
 #[cfg(test)]
@@ -141,8 +154,8 @@ mod sphere_tests {
         let xs = s.intersect(ray).expect("Must intersect");
 
         assert_eq!(xs.len(), 2);
-        assert_eq!(xs[0].obj, s);
-        assert_eq!(xs[1].obj, s);
+        assert!(std::ptr::addr_eq(xs[0].obj, &s));
+        assert!(std::ptr::addr_eq(xs[1].obj, &s));
     }
 
     #[test]
@@ -163,11 +176,25 @@ mod sphere_tests {
     }
 
     #[test]
-    fn intersecting_a_scaled_sphere_with_a_ray() {
-        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+    fn setting_a_transformation_composes_it_with_the_current_one() {
         let mut s = Sphere::default();
 
-        s.transform = BaseMatrix::identity().scale(2, 2, 2);
+        s.set_transform(BaseMatrix::identity().translate(2, 3, 4));
+        s.set_transform(BaseMatrix::identity().scale(2, 2, 2));
+
+        assert_eq!(
+            BaseMatrix::identity().scale(2, 2, 2).translate(2, 3, 4),
+            s.transform
+        );
+    }
+
+    #[test]
+    fn intersecting_a_scaled_sphere_with_a_ray() {
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let s = Sphere {
+            transform: BaseMatrix::identity().scale(2, 2, 2),
+            ..Default::default()
+        };
         let xs = s.intersect(r).expect("Must intersect");
 
         assert_eq!(2, xs.len());
@@ -179,9 +206,10 @@ mod sphere_tests {
     #[test]
     fn intersecting_a_translated_sphere_with_a_ray() {
         let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
-        let mut s = Sphere::default();
-
-        s.transform = BaseMatrix::identity().translate(5, 0, 0);
+        let s = Sphere {
+            transform: BaseMatrix::identity().translate(5, 0, 0),
+            ..Default::default()
+        };
         let xs = s.intersect(r);
 
         assert_eq!(None, xs);
@@ -236,8 +264,10 @@ mod sphere_tests {
 
     #[test]
     fn computing_normal_on_translated_sphere() {
-        let mut s = Sphere::default();
-        s.transform = BaseMatrix::identity().translate(0, 1, 0);
+        let s = Sphere {
+            transform: BaseMatrix::identity().translate(0, 1, 0),
+            ..Default::default()
+        };
 
         let n = s.normal_at(point(0, 1.70711, -FRAC_1_SQRT_2));
 
@@ -264,13 +294,24 @@ mod sphere_tests {
         assert_eq!(Material::default(), m);
     }
 
+    #[test]
+    fn a_helper_for_producing_a_sphere_with_a_glassy_material() {
+        let s = Sphere::glass();
+
+        assert_eq!(BaseMatrix::identity(), s.transform);
+        assert_eq!(1.0, s.material.transparency);
+        assert_eq!(1.5, s.material.refractive_index);
+    }
+
     #[test]
     fn sphere_may_be_assigned_a_material() {
         let mut s = Sphere::default();
-        let mut m = Material::default();
-        m.ambient = 1.0;
+        let m = Material {
+            ambient: 1.0,
+            ..Default::default()
+        };
 
-        s.material = m;
+        s.material = m.clone();
 
         assert_eq!(m, s.material);
     }
@@ -0,0 +1,164 @@
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_ray::Ray;
+use apolaki_tuple::Tuple;
+
+use crate::bounds::Bounds;
+use crate::intersect::{Intersection, Intersections};
+use crate::shape::Shape;
+use crate::EPSILON;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Triangle {
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    // edges from `p1`, and the face normal, computed once up front
+    pub e1: Tuple,
+    pub e2: Tuple,
+    pub normal: Tuple,
+    pub transform: BaseMatrix<4>,
+    pub material: Material,
+}
+
+impl Triangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        Self {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(e1).normalize(),
+            transform: BaseMatrix::identity(),
+            material: Material::default(),
+        }
+    }
+}
+
+// Möller–Trumbore: the `t` where the ray crosses the triangle, and the barycentric `u`
+// and `v` of that point
+pub(crate) fn intersect_triangle(
+    p1: Tuple,
+    e1: Tuple,
+    e2: Tuple,
+    ray: Ray,
+) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = ray.dir.cross(e2);
+    let det = e1.dot(dir_cross_e2);
+
+    // the ray is parallel to the triangle
+    if det.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1. / det;
+    let p1_to_origin = ray.origin - p1;
+    let u = f * p1_to_origin.dot(dir_cross_e2);
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(e1);
+    let v = f * ray.dir.dot(origin_cross_e1);
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+
+    Some((f * e2.dot(origin_cross_e1), u, v))
+}
+
+impl Shape for Triangle {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn bounds(&self) -> Bounds {
+        let mut bounds = Bounds::empty();
+        for p in [self.p1, self.p2, self.p3] {
+            bounds.add_point(p);
+        }
+        bounds
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let (t, u, v) = intersect_triangle(self.p1, self.e1, self.e2, ray)?;
+
+        Some(Intersections::from(vec![Intersection::with_uv(
+            t, self, u, v,
+        )]))
+    }
+
+    fn local_normal_at(&self, _at: Tuple) -> Tuple {
+        self.normal
+    }
+}
+
+#[cfg(test)]
+mod triangle_tests {
+    use apolaki_tuple::{point, vector};
+
+    use super::*;
+
+    fn test_triangle() -> Triangle {
+        Triangle::new(point(0, 1, 0), point(-1, 0, 0), point(1, 0, 0))
+    }
+
+    #[test]
+    fn constructing_a_triangle() {
+        let t = test_triangle();
+
+        assert_eq!(point(0, 1, 0), t.p1);
+        assert_eq!(point(-1, 0, 0), t.p2);
+        assert_eq!(point(1, 0, 0), t.p3);
+        assert_eq!(vector(-1, -1, 0), t.e1);
+        assert_eq!(vector(1, -1, 0), t.e2);
+        assert_eq!(vector(0, 0, -1), t.normal);
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_triangle() {
+        let t = test_triangle();
+
+        assert_eq!(t.normal, t.local_normal_at(point(0, 0.5, 0)));
+        assert_eq!(t.normal, t.local_normal_at(point(-0.5, 0.75, 0)));
+        assert_eq!(t.normal, t.local_normal_at(point(0.5, 0.25, 0)));
+    }
+
+    #[test]
+    fn intersecting_a_ray_parallel_to_the_triangle() {
+        let t = test_triangle();
+        let r = Ray::new(point(0, -1, -2), vector(0, 1, 0));
+
+        assert_eq!(None, t.local_intersect(r));
+    }
+
+    #[test]
+    fn a_ray_misses_the_edges_of_a_triangle() {
+        let t = test_triangle();
+
+        for origin in [point(1, 1, -2), point(-1, 1, -2), point(0, -1, -2)] {
+            let r = Ray::new(origin, vector(0, 0, 1));
+
+            assert_eq!(None, t.local_intersect(r));
+        }
+    }
+
+    #[test]
+    fn a_ray_strikes_a_triangle() {
+        let t = test_triangle();
+        let r = Ray::new(point(0, 0.5, -2), vector(0, 0, 1));
+
+        let xs = t.local_intersect(r).expect("must have intersections");
+
+        assert_eq!(1, xs.len());
+        assert_eq!(2., xs[0].t);
+    }
+}
@@ -0,0 +1,59 @@
+use apolaki_color::Color;
+use apolaki_tuple::Tuple;
+
+use crate::pattern::{Pair, Pattern, Rule};
+
+// the average of `a` and `b` everywhere, e.g. two crossed stripe patterns for a plaid
+pub type Blend = Pair<BlendRule>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BlendRule;
+
+impl Rule for BlendRule {
+    fn local_color_at(&self, a: &dyn Pattern, b: &dyn Pattern, at: Tuple) -> Color {
+        (a.color_at(at) + b.color_at(at)) * 0.5
+    }
+}
+
+#[cfg(test)]
+mod blend_tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use apolaki_matrix::BaseMatrix;
+    use apolaki_tuple::point;
+
+    use super::*;
+    use crate::Stripe;
+
+    #[test]
+    fn blending_averages_two_patterns() {
+        let pattern = Blend::new(Color::new(1, 0.5, 0), Color::new(0, 0.5, 1));
+
+        assert_eq!(
+            Color::new(0.5, 0.5, 0.5),
+            pattern.local_color_at(point(3, -2, 1))
+        );
+    }
+
+    #[test]
+    fn blending_crossed_stripes() {
+        let green = Color::new(0, 1, 0);
+        let pattern = Blend::new(
+            Stripe::new(green, Color::WHITE),
+            Stripe {
+                transform: BaseMatrix::identity().rotate_y(FRAC_PI_2),
+                ..Stripe::new(green, Color::WHITE)
+            },
+        );
+        let cases = [
+            (point(0.5, 0, -0.5), green),
+            (point(1.5, 0, -0.5), Color::new(0.5, 1, 0.5)),
+            (point(0.5, 0, 0.5), Color::new(0.5, 1, 0.5)),
+            (point(1.5, 0, 0.5), Color::WHITE),
+        ];
+
+        for (at, color) in cases {
+            assert_eq!(color, pattern.local_color_at(at), "{at:?}");
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use apolaki_color::Color;
+use apolaki_tuple::Tuple;
+
+use crate::pattern::{Pair, Pattern, Rule};
+
+// unit cubes alternating between `a` and `b` in all three dimensions
+pub type Checker = Pair<CheckerRule>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CheckerRule;
+
+impl Rule for CheckerRule {
+    fn local_color_at(&self, a: &dyn Pattern, b: &dyn Pattern, at: Tuple) -> Color {
+        let sum = at.x().floor() + at.y().floor() + at.z().floor();
+        if (sum as i64).rem_euclid(2) == 0 {
+            a.color_at(at)
+        } else {
+            b.color_at(at)
+        }
+    }
+}
+
+#[cfg(test)]
+mod checker_tests {
+    use apolaki_tuple::point;
+
+    use super::*;
+
+    #[test]
+    fn checkers_should_repeat_in_each_dimension() {
+        let pattern = Checker::new(Color::WHITE, Color::BLACK);
+        let cases = [
+            (point(0, 0, 0), Color::WHITE),
+            (point(0.99, 0, 0), Color::WHITE),
+            (point(1.01, 0, 0), Color::BLACK),
+            (point(0, 0.99, 0), Color::WHITE),
+            (point(0, 1.01, 0), Color::BLACK),
+            (point(0, 0, 0.99), Color::WHITE),
+            (point(0, 0, 1.01), Color::BLACK),
+        ];
+
+        for (at, color) in cases {
+            assert_eq!(color, pattern.local_color_at(at));
+        }
+    }
+}
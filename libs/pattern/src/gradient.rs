@@ -0,0 +1,54 @@
+use apolaki_color::Color;
+use apolaki_tuple::Tuple;
+
+use crate::pattern::{Pair, Pattern, Rule};
+
+// blends from `a` to `b` along x, repeating every unit
+pub type Gradient = Pair<GradientRule>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GradientRule;
+
+impl Rule for GradientRule {
+    fn local_color_at(&self, a: &dyn Pattern, b: &dyn Pattern, at: Tuple) -> Color {
+        let fraction = at.x() - at.x().floor();
+        let (a, b) = (a.color_at(at), b.color_at(at));
+        a + (b - a) * fraction
+    }
+}
+
+#[cfg(test)]
+mod gradient_tests {
+    use apolaki_tuple::point;
+
+    use super::*;
+
+    #[test]
+    fn a_gradient_linearly_interpolates_between_colors() {
+        let pattern = Gradient::new(Color::WHITE, Color::BLACK);
+        let cases = [
+            (0., Color::WHITE),
+            (0.25, Color::new(0.75, 0.75, 0.75)),
+            (0.5, Color::new(0.5, 0.5, 0.5)),
+            (0.75, Color::new(0.25, 0.25, 0.25)),
+        ];
+
+        for (x, color) in cases {
+            assert_eq!(color, pattern.local_color_at(point(x, 0, 0)));
+        }
+    }
+
+    #[test]
+    fn a_gradient_repeats_every_unit() {
+        let pattern = Gradient::new(Color::WHITE, Color::BLACK);
+
+        assert_eq!(
+            Color::new(0.75, 0.75, 0.75),
+            pattern.local_color_at(point(1.25, 0, 0))
+        );
+        assert_eq!(
+            Color::new(0.75, 0.75, 0.75),
+            pattern.local_color_at(point(-0.75, 0, 0))
+        );
+    }
+}
@@ -0,0 +1,17 @@
+pub use blend::*;
+pub use checker::*;
+pub use gradient::*;
+pub use noise::*;
+pub use pattern::*;
+pub use perturb::*;
+pub use ring::*;
+pub use stripe::*;
+
+mod blend;
+mod checker;
+mod gradient;
+mod noise;
+mod pattern;
+mod perturb;
+mod ring;
+mod stripe;
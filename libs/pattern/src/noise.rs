@@ -0,0 +1,146 @@
+use apolaki_random::Rng;
+
+// How many lattice cells the noise covers before it repeats; must be a power of two.
+const PERIOD: usize = 256;
+
+// Ken Perlin's improved gradient noise in three dimensions: smooth, roughly within [-1, 1],
+// and 0 on every integer lattice point. The lattice's gradients are picked by a
+// permutation shuffled from `seed`, so the same seed always gives the same noise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Perlin {
+    // the permutation twice over, so lookups of `p[p[x] + y]` never need wrapping
+    permutation: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let mut permutation: Vec<usize> = (0..PERIOD).collect();
+        // Fisher-Yates
+        for i in (1..PERIOD).rev() {
+            let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+            permutation.swap(i, j);
+        }
+        permutation.extend_from_within(..);
+
+        Self { permutation }
+    }
+
+    pub fn noise(&self, at: (f64, f64, f64)) -> f64 {
+        let (x, y, z) = at;
+        let p = &self.permutation;
+
+        // the lattice cell containing the point, and where the point sits within it
+        let cell = |v: f64| (v.floor() as i64).rem_euclid(PERIOD as i64) as usize;
+        let (xi, yi, zi) = (cell(x), cell(y), cell(z));
+        let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        // hashes of the cell's eight corners
+        let a = p[xi] + yi;
+        let (aa, ab) = (p[a] + zi, p[a + 1] + zi);
+        let b = p[xi + 1] + yi;
+        let (ba, bb) = (p[b] + zi, p[b + 1] + zi);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1., y, z)),
+                lerp(u, grad(p[ab], x, y - 1., z), grad(p[bb], x - 1., y - 1., z)),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.),
+                    grad(p[ba + 1], x - 1., y, z - 1.),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1., z - 1.),
+                    grad(p[bb + 1], x - 1., y - 1., z - 1.),
+                ),
+            ),
+        )
+    }
+}
+
+// eases `t` in [0, 1] so the noise's first and second derivatives are continuous across cells
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+// the dot product of the offset with one of twelve gradients along the cube's edges, picked
+// by `hash`
+fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    let u = if h & 1 == 0 { u } else { -u };
+    let v = if h & 2 == 0 { v } else { -v };
+    u + v
+}
+
+#[cfg(test)]
+mod noise_tests {
+    use super::*;
+
+    fn samples() -> impl Iterator<Item = (f64, f64, f64)> {
+        (0..1000).map(|i| {
+            let i = i as f64;
+            (i * 0.37 - 150., i * 0.11 + 0.5, -i * 0.23)
+        })
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_noise() {
+        let (a, b) = (Perlin::new(7), Perlin::new(7));
+
+        assert_eq!(a, b);
+        assert!(samples().all(|at| a.noise(at) == b.noise(at)));
+    }
+
+    #[test]
+    fn different_seeds_give_different_noise() {
+        let (a, b) = (Perlin::new(1), Perlin::new(2));
+
+        assert!(samples().any(|at| a.noise(at) != b.noise(at)));
+    }
+
+    #[test]
+    fn noise_is_zero_on_the_lattice() {
+        let perlin = Perlin::new(0);
+
+        for at in [(0., 0., 0.), (1., 2., 3.), (-4., 17., -300.)] {
+            assert_eq!(0., perlin.noise(at));
+        }
+    }
+
+    #[test]
+    fn noise_is_bounded_and_varies() {
+        let perlin = Perlin::new(0);
+        let values: Vec<_> = samples().map(|at| perlin.noise(at)).collect();
+
+        assert!(values.iter().all(|n| (-1. ..=1.).contains(n)));
+        assert!(values.iter().any(|n| n.abs() > 0.1));
+    }
+
+    #[test]
+    fn noise_is_continuous() {
+        let perlin = Perlin::new(0);
+
+        for (x, y, z) in samples() {
+            let step = perlin.noise((x, y, z)) - perlin.noise((x + 0.001, y, z - 0.001));
+            assert!(step.abs() < 0.01, "{x} {y} {z}");
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use apolaki_color::Color;
+use apolaki_matrix::{BaseMatrix, Invert};
+use apolaki_tuple::Tuple;
+
+// a color for every point, placed on the object it colors by `transform`
+pub trait Pattern: Debug + Send + Sync {
+    fn transform(&self) -> BaseMatrix<4>;
+
+    // the color at a point that is already in pattern space
+    fn local_color_at(&self, at: Tuple) -> Color;
+
+    // the color at a point in object space
+    fn color_at(&self, object_point: Tuple) -> Color {
+        self.local_color_at(self.transform().invert() * object_point)
+    }
+}
+
+// a plain color is a pattern that is the same everywhere, so it can be used wherever
+// another pattern takes its inputs
+impl Pattern for Color {
+    fn transform(&self) -> BaseMatrix<4> {
+        BaseMatrix::identity()
+    }
+
+    fn local_color_at(&self, _at: Tuple) -> Color {
+        *self
+    }
+}
+
+// lets one pattern be shared as an input to several others
+impl<P: Pattern + ?Sized> Pattern for Arc<P> {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.as_ref().transform()
+    }
+
+    fn local_color_at(&self, at: Tuple) -> Color {
+        self.as_ref().local_color_at(at)
+    }
+
+    fn color_at(&self, object_point: Tuple) -> Color {
+        self.as_ref().color_at(object_point)
+    }
+}
+
+// how a `Pair` picks between, or mixes, its inputs at a point in pattern space
+pub trait Rule: Debug + Default + Send + Sync {
+    fn local_color_at(&self, a: &dyn Pattern, b: &dyn Pattern, at: Tuple) -> Color;
+}
+
+// a pattern made from two others; `Stripe`, `Checker` and the rest differ only in `rule`
+#[derive(Clone, Debug)]
+pub struct Pair<R> {
+    pub a: Arc<dyn Pattern>,
+    pub b: Arc<dyn Pattern>,
+    pub transform: BaseMatrix<4>,
+    pub rule: R,
+}
+
+impl<R: Rule> Pair<R> {
+    pub fn new(a: impl Pattern + 'static, b: impl Pattern + 'static) -> Self {
+        Self {
+            a: Arc::new(a),
+            b: Arc::new(b),
+            transform: BaseMatrix::identity(),
+            rule: R::default(),
+        }
+    }
+}
+
+impl<R: Rule> Pattern for Pair<R> {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn local_color_at(&self, at: Tuple) -> Color {
+        self.rule
+            .local_color_at(self.a.as_ref(), self.b.as_ref(), at)
+    }
+}
+
+#[cfg(test)]
+mod pattern_tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use apolaki_tuple::point;
+
+    use super::*;
+    use crate::{Checker, Stripe};
+
+    // colors each point with its own coordinates
+    #[derive(Debug)]
+    struct TestPattern {
+        transform: BaseMatrix<4>,
+    }
+
+    impl Pattern for TestPattern {
+        fn transform(&self) -> BaseMatrix<4> {
+            self.transform
+        }
+
+        fn local_color_at(&self, at: Tuple) -> Color {
+            Color::new(at.x(), at.y(), at.z())
+        }
+    }
+
+    #[test]
+    fn a_pattern_with_a_transformation() {
+        let pattern = TestPattern {
+            transform: BaseMatrix::identity().scale(2, 2, 2),
+        };
+
+        assert_eq!(Color::new(1, 1.5, 2), pattern.color_at(point(2, 3, 4)));
+    }
+
+    #[test]
+    fn a_color_is_the_same_everywhere() {
+        let red = Color::new(1, 0, 0);
+
+        for at in [point(0, 0, 0), point(-3.5, 2, 10), point(0.5, 0.5, -0.5)] {
+            assert_eq!(red, red.color_at(at));
+        }
+    }
+
+    #[test]
+    fn a_shared_pattern_keeps_its_transformation() {
+        let pattern: Arc<dyn Pattern> = Arc::new(TestPattern {
+            transform: BaseMatrix::identity().scale(2, 2, 2),
+        });
+
+        assert_eq!(Color::new(1, 1.5, 2), pattern.color_at(point(2, 3, 4)));
+    }
+
+    #[test]
+    fn a_pattern_with_an_untransformed_point() {
+        let pattern = TestPattern {
+            transform: BaseMatrix::identity().translate(0.5, 1, 1.5),
+        };
+
+        assert_eq!(
+            Color::new(0.75, 0.5, 0.25),
+            pattern.color_at(point(1.25, 1.5, 1.75))
+        );
+    }
+
+    #[test]
+    fn creating_a_pair_of_patterns() {
+        let pattern = Stripe::new(Color::WHITE, Color::BLACK);
+
+        assert_eq!(BaseMatrix::identity(), pattern.transform);
+        assert_eq!(Color::WHITE, pattern.a.color_at(point(0, 0, 0)));
+        assert_eq!(Color::BLACK, pattern.b.color_at(point(0, 0, 0)));
+    }
+
+    #[test]
+    fn a_pairs_inputs_can_be_patterns_themselves() {
+        let red = Color::new(1, 0, 0);
+        let blue = Color::new(0, 0, 1);
+        let pattern = Checker::new(
+            Stripe::new(Color::WHITE, Color::BLACK),
+            Stripe {
+                transform: BaseMatrix::identity().scale(0.5, 1, 1),
+                ..Stripe::new(red, blue)
+            },
+        );
+        let cases = [
+            (point(0.5, 0, 0), Color::WHITE),
+            (point(1.5, 1, 0), Color::BLACK),
+            (point(1.25, 0, 0), red),
+            (point(1.75, 0, 0), blue),
+        ];
+
+        for (at, color) in cases {
+            assert_eq!(color, pattern.local_color_at(at), "{at:?}");
+        }
+    }
+
+    #[test]
+    fn a_pairs_inputs_apply_their_own_transformations() {
+        // the second input is itself striped black and red, every half unit along z
+        let inner = Stripe {
+            transform: BaseMatrix::identity().scale(0.5, 1, 1).rotate_y(FRAC_PI_2),
+            ..Stripe::new(Color::BLACK, Color::new(1, 0, 0))
+        };
+        let pattern = Stripe::new(Color::WHITE, inner);
+
+        assert_eq!(Color::WHITE, pattern.local_color_at(point(0.5, 0, 0.75)));
+        assert_eq!(Color::BLACK, pattern.local_color_at(point(1.5, 0, -0.25)));
+        assert_eq!(
+            Color::new(1, 0, 0),
+            pattern.local_color_at(point(1.5, 0, 0.25))
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use apolaki_color::Color;
+use apolaki_matrix::BaseMatrix;
+use apolaki_tuple::{vector, Tuple};
+
+use crate::noise::Perlin;
+use crate::pattern::Pattern;
+
+// `pattern`, with each point jittered by up to `scale` in every direction by Perlin noise
+// before it's looked up, which turns clean stripes and rings into marble and wood grain
+#[derive(Clone, Debug)]
+pub struct Perturb {
+    pub pattern: Arc<dyn Pattern>,
+    pub noise: Perlin,
+    pub scale: f64,
+    pub transform: BaseMatrix<4>,
+}
+
+impl Perturb {
+    pub fn new(pattern: impl Pattern + 'static, seed: u64) -> Self {
+        Self {
+            pattern: Arc::new(pattern),
+            noise: Perlin::new(seed),
+            scale: 0.2,
+            transform: BaseMatrix::identity(),
+        }
+    }
+}
+
+impl Pattern for Perturb {
+    fn transform(&self) -> BaseMatrix<4> {
+        self.transform
+    }
+
+    fn local_color_at(&self, at: Tuple) -> Color {
+        let (x, y, z) = (at.x(), at.y(), at.z());
+        // the same noise sampled at far-apart offsets gives three unrelated components
+        let jitter = vector(
+            self.noise.noise((x, y, z)),
+            self.noise.noise((x + 31.4, y + 15.9, z + 26.5)),
+            self.noise.noise((x + 35.8, y + 97.9, z + 32.3)),
+        );
+
+        self.pattern.color_at(at + jitter * self.scale)
+    }
+}
+
+#[cfg(test)]
+mod perturb_tests {
+    use apolaki_tuple::point;
+
+    use super::*;
+    use crate::{Ring, Stripe};
+
+    fn samples() -> impl Iterator<Item = Tuple> {
+        (0..200).map(|i| {
+            let i = i as f64;
+            point(i * 0.13 - 10., i * 0.07, 5. - i * 0.05)
+        })
+    }
+
+    #[test]
+    fn perturbing_by_nothing_leaves_the_pattern_alone() {
+        let stripes = Stripe::new(Color::WHITE, Color::BLACK);
+        let pattern = Perturb {
+            scale: 0.,
+            ..Perturb::new(stripes.clone(), 1)
+        };
+
+        for at in samples() {
+            assert_eq!(stripes.color_at(at), pattern.local_color_at(at));
+        }
+    }
+
+    #[test]
+    fn perturbing_moves_the_pattern_around() {
+        let stripes = Stripe::new(Color::WHITE, Color::BLACK);
+        let pattern = Perturb::new(stripes.clone(), 1);
+
+        assert!(samples().any(|at| stripes.color_at(at) != pattern.local_color_at(at)));
+    }
+
+    #[test]
+    fn perturbing_with_the_same_seed_is_repeatable() {
+        let a = Perturb::new(Ring::new(Color::WHITE, Color::BLACK), 99);
+        let b = Perturb::new(Ring::new(Color::WHITE, Color::BLACK), 99);
+
+        for at in samples() {
+            assert_eq!(a.local_color_at(at), b.local_color_at(at));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use apolaki_color::Color;
+use apolaki_tuple::Tuple;
+
+use crate::pattern::{Pair, Pattern, Rule};
+
+// concentric rings around the y axis, one unit apart, alternating between `a` and `b`
+pub type Ring = Pair<RingRule>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RingRule;
+
+impl Rule for RingRule {
+    fn local_color_at(&self, a: &dyn Pattern, b: &dyn Pattern, at: Tuple) -> Color {
+        let distance = (at.x().powi(2) + at.z().powi(2)).sqrt();
+        if (distance.floor() as i64).rem_euclid(2) == 0 {
+            a.color_at(at)
+        } else {
+            b.color_at(at)
+        }
+    }
+}
+
+#[cfg(test)]
+mod ring_tests {
+    use apolaki_tuple::point;
+
+    use super::*;
+
+    #[test]
+    fn a_ring_should_extend_in_both_x_and_z() {
+        let pattern = Ring::new(Color::WHITE, Color::BLACK);
+        let cases = [
+            (point(0, 0, 0), Color::WHITE),
+            (point(1, 0, 0), Color::BLACK),
+            (point(0, 0, 1), Color::BLACK),
+            // 0.708 is just over √2/2
+            (point(0.708, 0, 0.708), Color::BLACK),
+        ];
+
+        for (at, color) in cases {
+            assert_eq!(color, pattern.local_color_at(at));
+        }
+    }
+}
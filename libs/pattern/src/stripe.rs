@@ -0,0 +1,73 @@
+use apolaki_color::Color;
+use apolaki_tuple::Tuple;
+
+use crate::pattern::{Pair, Pattern, Rule};
+
+// stripes one unit wide along x, alternating between `a` and `b`
+pub type Stripe = Pair<StripeRule>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StripeRule;
+
+impl Rule for StripeRule {
+    fn local_color_at(&self, a: &dyn Pattern, b: &dyn Pattern, at: Tuple) -> Color {
+        if (at.x().floor() as i64).rem_euclid(2) == 0 {
+            a.color_at(at)
+        } else {
+            b.color_at(at)
+        }
+    }
+}
+
+#[cfg(test)]
+mod stripe_tests {
+    use apolaki_matrix::BaseMatrix;
+    use apolaki_tuple::point;
+
+    use super::*;
+
+    fn stripes() -> Stripe {
+        Stripe::new(Color::WHITE, Color::BLACK)
+    }
+
+    #[test]
+    fn a_stripe_pattern_is_constant_in_y_and_z() {
+        let pattern = stripes();
+
+        for at in [
+            point(0, 1, 0),
+            point(0, 2, 0),
+            point(0, 0, 1),
+            point(0, 0, 2),
+        ] {
+            assert_eq!(Color::WHITE, pattern.local_color_at(at));
+        }
+    }
+
+    #[test]
+    fn a_stripe_pattern_alternates_in_x() {
+        let pattern = stripes();
+        let cases = [
+            (0., Color::WHITE),
+            (0.9, Color::WHITE),
+            (1., Color::BLACK),
+            (-0.1, Color::BLACK),
+            (-1., Color::BLACK),
+            (-1.1, Color::WHITE),
+        ];
+
+        for (x, color) in cases {
+            assert_eq!(color, pattern.local_color_at(point(x, 0, 0)));
+        }
+    }
+
+    #[test]
+    fn stripes_with_a_pattern_transformation() {
+        let pattern = Stripe {
+            transform: BaseMatrix::identity().scale(2, 2, 2),
+            ..stripes()
+        };
+
+        assert_eq!(Color::WHITE, pattern.color_at(point(1.5, 0, 0)));
+    }
+}
@@ -1,72 +1,895 @@
 use apolaki_color::Color;
 use apolaki_light::Light;
-use apolaki_objects::{Intersect, Sphere};
-use apolaki_tuple::point;
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use std::sync::OnceLock;
 
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
-pub struct World<'a, O: Intersect> {
-    objs: &'a [O],
-    light: Option<Light>,
+use apolaki_objects::{
+    prepare_computations, Bvh, BvhSplit, Computations, Intersections, Shape, Sphere,
+};
+use apolaki_ray::Ray;
+use apolaki_tuple::{point, Tuple};
+
+// how many times a ray may bounce between reflective surfaces before giving up
+pub const DEFAULT_MAX_DEPTH: usize = 5;
+
+#[derive(Debug)]
+pub struct World {
+    objects: Vec<Box<dyn Shape>>,
+    lights: Vec<Light>,
+    max_depth: usize,
+    bvh_split: BvhSplit,
+    // built over the objects the first time a ray is cast
+    bvh: OnceLock<Bvh>,
 }
-impl<'a, O: Intersect> World<'a, O> {
+
+impl World {
     pub fn new() -> Self {
         Self {
-            objs: &[],
-            light: None,
+            objects: Vec::new(),
+            lights: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            bvh_split: BvhSplit::default(),
+            bvh: OnceLock::new(),
         }
     }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth
+    }
+
     pub fn size(&self) -> usize {
-        self.objs.len()
+        self.objects.len()
+    }
+
+    pub fn objects(&self) -> &[Box<dyn Shape>] {
+        &self.objects
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn add_object<S: Shape + 'static>(&mut self, object: S) {
+        self.objects.push(Box::new(object));
+        self.bvh.take();
+    }
+
+    pub fn bvh_split(&self) -> BvhSplit {
+        self.bvh_split
     }
 
-    pub fn light(&self) -> Option<&Light> {
-        self.light.as_ref()
+    pub fn set_bvh_split(&mut self, split: BvhSplit) {
+        self.bvh_split = split;
+        self.bvh.take();
+    }
+
+    // the hierarchy over the objects' bounds, in world space
+    pub fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| {
+            let bounds: Vec<_> = self
+                .objects
+                .iter()
+                .map(|o| o.parent_space_bounds())
+                .collect();
+            Bvh::build(&bounds, self.bvh_split)
+        })
+    }
+
+    pub fn add_light(&mut self, light: Light) {
+        self.lights.push(light)
+    }
+
+    // every object's intersections merged into a single list, sorted by `t`; objects
+    // whose bounds the ray misses are skipped
+    pub fn intersect(&self, ray: Ray) -> Intersections<'_> {
+        self.bvh()
+            .candidates(ray)
+            .into_iter()
+            .filter_map(|i| self.objects[i].intersect(ray))
+            .flatten()
+            .collect()
+    }
+
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Color {
+        let material = comps.obj.material();
+        let surface = self
+            .lights
+            .iter()
+            .map(|light| {
+                let intensity = self.intensity_at(comps.over_point, light);
+                material.lighting(
+                    light,
+                    comps.over_point,
+                    comps.object_point,
+                    comps.eyev,
+                    comps.normalv,
+                    intensity,
+                )
+            })
+            .fold(Color::BLACK, |acc, c| acc + c);
+
+        let reflected = self.reflected_color(comps, remaining);
+        let refracted = self.refracted_color(comps, remaining);
+
+        if material.reflective > 0. && material.transparency > 0. {
+            // blend by how much light the surface reflects at this angle
+            let reflectance = comps.schlick();
+            surface + reflected * reflectance + refracted * (1. - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
+    }
+
+    // the color seen along the reflection vector, scaled by how reflective the surface is
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let reflective = comps.obj.material().reflective;
+        if remaining == 0 || reflective == 0. {
+            return Color::BLACK;
+        }
+
+        let reflect_ray = Ray::new(comps.over_point, comps.reflectv);
+        self.color_at_depth(reflect_ray, remaining - 1) * reflective
+    }
+
+    // the color seen through a transparent surface, bent by Snell's law
+    pub fn refracted_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let transparency = comps.obj.material().transparency;
+        if remaining == 0 || transparency == 0. {
+            return Color::BLACK;
+        }
+
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot(comps.normalv);
+        let sin2_t = n_ratio.powi(2) * (1. - cos_i.powi(2));
+        if sin2_t > 1. {
+            // total internal reflection
+            return Color::BLACK;
+        }
+
+        let cos_t = (1. - sin2_t).sqrt();
+        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+        let refract_ray = Ray::new(comps.under_point, direction);
+
+        self.color_at_depth(refract_ray, remaining - 1) * transparency
+    }
+
+    // whether something blocks every one of the light's samples from `point`
+    pub fn is_shadowed(&self, point: Tuple, light: &Light) -> bool {
+        light
+            .incidences(point)
+            .iter()
+            .all(|incidence| self.is_occluded(point, incidence.light_vector, incidence.distance))
+    }
+
+    // The fraction of the light's samples that reach `point`: those that are unblocked and
+    // light it at all, so points outside a spot's cone or beyond a light's range get 0.
+    pub fn intensity_at(&self, point: Tuple, light: &Light) -> f64 {
+        let incidences = light.incidences(point);
+        let lit = incidences
+            .iter()
+            // samples that light nothing, like those beyond a light's range or outside a
+            // spot's cone, need no shadow ray
+            .filter(|incidence| incidence.attenuation > 0.)
+            .filter(|incidence| {
+                !self.is_occluded(point, incidence.light_vector, incidence.distance)
+            })
+            .count();
+
+        lit as f64 / incidences.len() as f64
+    }
+
+    // whether anything sits within `distance` of `point` along `direction`
+    fn is_occluded(&self, point: Tuple, direction: Tuple, distance: f64) -> bool {
+        let ray = Ray::new(point, direction);
+
+        self.intersect(ray)
+            .hit()
+            .is_some_and(|hit| hit.t < distance)
+    }
+
+    pub fn color_at(&self, ray: Ray) -> Color {
+        self.color_at_depth(ray, self.max_depth)
+    }
+
+    // like `color_at`, but with `remaining` bounces left for reflected rays
+    pub fn color_at_depth(&self, ray: Ray, remaining: usize) -> Color {
+        let xs = self.intersect(ray);
+        match xs.hit() {
+            Some(hit) => self.shade_hit(&prepare_computations(hit, &ray, &xs), remaining),
+            None => Color::BLACK,
+        }
     }
 }
-impl<'a, O: Intersect> Default for World<'a, O> {
-    fn default() -> Self {
-        let mut s1 = Sphere::default();
 
-        let mut s2 = Sphere::default();
+impl Default for World {
+    // two concentric spheres lit from the upper left
+    fn default() -> Self {
+        let s1 = Sphere {
+            material: Material {
+                color: Color::new(0.8, 1.0, 0.6),
+                diffuse: 0.7,
+                specular: 0.2,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let s2 = Sphere {
+            transform: BaseMatrix::identity().scale(0.5, 0.5, 0.5),
+            ..Default::default()
+        };
         let light = Light::new(point(-10, 10, -10), Color::new(1, 1, 1));
-        Self {
-            objs: &[s1, s2],
-            light: Some(light),
-        }
+
+        let mut w = Self::new();
+        w.add_object(s1);
+        w.add_object(s2);
+        w.add_light(light);
+        w
     }
 }
+
 #[cfg(test)]
 mod tests {
+    use std::f64::consts::{PI, SQRT_2};
+    use std::sync::Arc;
+
+    use apolaki_light::Falloff;
+    use apolaki_objects::{Intersection, Plane};
+    use apolaki_pattern::{Pattern, Stripe};
+    use apolaki_tuple::vector;
+
     use super::*;
 
+    // colors each point with its own coordinates
+    #[derive(Debug)]
+    struct TestPattern;
+
+    impl Pattern for TestPattern {
+        fn transform(&self) -> BaseMatrix<4> {
+            BaseMatrix::identity()
+        }
+
+        fn local_color_at(&self, at: Tuple) -> Color {
+            Color::new(at.x(), at.y(), at.z())
+        }
+    }
+
     #[test]
     fn creating_a_world() {
         let w = World::new();
 
         assert_eq!(0, w.size());
-        assert_eq!(None, w.light());
+        assert!(w.lights().is_empty());
     }
 
     #[test]
     fn default_world() {
-        let light = Light(point(-10, 10, -10), Color::new(1, 1, 1));
+        let light = Light::new(point(-10, 10, -10), Color::new(1, 1, 1));
 
         let m = Material {
             color: Color::new(0.8, 1.0, 0.6),
             diffuse: 0.7,
-            spcular: 0.2,
+            specular: 0.2,
             ..Default::default()
         };
 
         let w = World::default();
+
+        assert_eq!(2, w.size());
+        assert_eq!(light, w.lights()[0]);
+        assert_eq!(&m, w.objects()[0].material());
+        assert_eq!(&Material::default(), w.objects()[1].material());
     }
+
     #[test]
     fn intersect_world_with_a_ray() {
         let w = World::default();
         let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
 
-        let xs = w.hit(ray);
+        let xs = w.intersect(r);
+
+        assert_eq!(4, xs.len());
+        assert_eq!(4.0, xs[0].t);
+        assert_eq!(4.5, xs[1].t);
+        assert_eq!(5.5, xs[2].t);
+        assert_eq!(6.0, xs[3].t);
+    }
+
+    #[test]
+    fn shading_an_intersection() {
+        let w = World::default();
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let shape = w.objects()[0].as_ref();
+        let i = Intersection::new(4, shape);
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+        let c = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), c);
+    }
+
+    #[test]
+    fn shading_an_intersection_from_the_inside() {
+        let w = World {
+            lights: vec![Light::new(point(0, 0.25, 0), Color::new(1, 1, 1))],
+            ..Default::default()
+        };
+        let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
+        let shape = w.objects()[1].as_ref();
+        let i = Intersection::new(0.5, shape);
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+        let c = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::new(0.90498, 0.90498, 0.90498), c);
+    }
+
+    #[test]
+    fn shading_an_intersection_sums_every_light() {
+        let mut w = World::default();
+        let light = w.lights()[0];
+        w.add_light(light);
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let shape = w.objects()[0].as_ref();
+        let i = Intersection::new(4, shape);
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+        let c = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855) * 2, c);
+    }
+
+    #[test]
+    fn the_color_when_a_ray_misses() {
+        let w = World::default();
+        let r = Ray::new(point(0, 0, -5), vector(0, 1, 0));
+
+        let c = w.color_at(r);
+
+        assert_eq!(Color::BLACK, c);
+    }
+
+    #[test]
+    fn the_color_when_a_ray_hits() {
+        let w = World::default();
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+
+        let c = w.color_at(r);
+
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), c);
+    }
+
+    #[test]
+    fn the_color_with_an_intersection_behind_the_ray() {
+        let outer = Sphere {
+            material: Material {
+                color: Color::new(0.8, 1.0, 0.6),
+                ambient: 1.0,
+                diffuse: 0.7,
+                specular: 0.2,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let inner = Sphere {
+            transform: BaseMatrix::identity().scale(0.5, 0.5, 0.5),
+            material: Material {
+                ambient: 1.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut w = World::new();
+        let inner_color = inner.material.color;
+        w.add_object(outer);
+        w.add_object(inner);
+        w.add_light(Light::new(point(-10, 10, -10), Color::new(1, 1, 1)));
+        let r = Ray::new(point(0, 0, 0.75), vector(0, 0, -1));
+
+        let c = w.color_at(r);
+
+        assert_eq!(inner_color, c);
+    }
+
+    #[test]
+    fn there_is_no_shadow_when_nothing_is_collinear_with_point_and_light() {
+        let w = World::default();
+        let p = point(0, 10, 0);
+
+        assert!(!w.is_shadowed(p, &w.lights()[0]));
+    }
+
+    #[test]
+    fn the_shadow_when_an_object_is_between_the_point_and_the_light() {
+        let w = World::default();
+        let p = point(10, -10, 10);
+
+        assert!(w.is_shadowed(p, &w.lights()[0]));
+    }
+
+    #[test]
+    fn there_is_no_shadow_when_an_object_is_behind_the_light() {
+        let w = World::default();
+        let p = point(-20, 20, -20);
+
+        assert!(!w.is_shadowed(p, &w.lights()[0]));
+    }
+
+    #[test]
+    fn there_is_no_shadow_when_an_object_is_behind_the_point() {
+        let w = World::default();
+        let p = point(-2, 2, -2);
+
+        assert!(!w.is_shadowed(p, &w.lights()[0]));
+    }
+
+    #[test]
+    fn point_lights_evaluate_the_light_intensity_at_a_given_point() {
+        let w = World::default();
+        let light = w.lights()[0];
+        let cases = [
+            (point(0, 1.0001, 0), 1.0),
+            (point(-1.0001, 0, 0), 1.0),
+            (point(0, 0, -1.0001), 1.0),
+            (point(0, 0, 1.0001), 0.0),
+            (point(1.0001, 0, 0), 0.0),
+            (point(0, -1.0001, 0), 0.0),
+            (point(0, 0, 0), 0.0),
+        ];
+
+        for (p, expected) in cases {
+            assert_eq!(expected, w.intensity_at(p, &light));
+        }
+    }
+
+    #[test]
+    fn the_area_light_intensity_function() {
+        let w = World::default();
+        let light = Light::area(
+            point(-0.5, -0.5, -5),
+            vector(1, 0, 0),
+            2,
+            vector(0, 1, 0),
+            2,
+            Color::WHITE,
+        );
+        let cases = [
+            (point(0, 0, 2), 0.0),
+            (point(1, -1, 2), 0.25),
+            (point(1.5, 0, 2), 0.5),
+            (point(1.25, 1.25, 3), 0.75),
+            (point(0, 0, -2), 1.0),
+        ];
+
+        for (p, expected) in cases {
+            assert_eq!(expected, w.intensity_at(p, &light));
+        }
+    }
+
+    #[test]
+    fn an_area_light_casts_a_soft_shadow() {
+        let w = World::default();
+        let light = Light::area(
+            point(-0.5, -0.5, -5),
+            vector(1, 0, 0),
+            2,
+            vector(0, 1, 0),
+            2,
+            Color::WHITE,
+        );
+
+        // partially lit, so not "in shadow"
+        assert!(!w.is_shadowed(point(1.5, 0, 2), &light));
+        assert!(w.is_shadowed(point(0, 0, 2), &light));
+    }
+
+    #[test]
+    fn a_directional_light_is_blocked_by_anything_towards_it() {
+        let w = World::default();
+        let light = Light::directional(vector(0, -1, 0), Color::WHITE);
+
+        assert!(w.is_shadowed(point(0, -2, 0), &light));
+        assert!(w.is_shadowed(point(0, -1000, 0), &light));
+        assert!(!w.is_shadowed(point(0, 2, 0), &light));
+        assert!(!w.is_shadowed(point(5, -2, 0), &light));
+    }
+
+    #[test]
+    fn lights_do_not_reach_beyond_their_range() {
+        let w = World::default();
+        let light = Light::new(point(-10, 10, -10), Color::WHITE)
+            .with_falloff(Falloff::inverse_square(20.));
+
+        assert_eq!(1., w.intensity_at(point(-10, 0, -10), &light));
+        assert_eq!(0., w.intensity_at(point(10, 10, 10), &light));
+    }
+
+    #[test]
+    fn points_outside_a_spots_cone_are_unlit_but_not_shadowed() {
+        let w = World::default();
+        let light = Light::spot(
+            point(0, 10, 0),
+            vector(0, -1, 0),
+            PI / 8.,
+            PI / 6.,
+            Color::WHITE,
+        );
+        let outside_cone = point(10, 0, 0);
+
+        assert!(!w.is_shadowed(outside_cone, &light));
+        assert_eq!(0., w.intensity_at(outside_cone, &light));
+        assert_eq!(1., w.intensity_at(point(0, 2, 0), &light));
+    }
+
+    #[test]
+    fn shade_hit_is_given_an_intersection_in_shadow() {
+        let mut w = World::new();
+        w.add_light(Light::new(point(0, 0, -10), Color::new(1, 1, 1)));
+        w.add_object(Sphere::default());
+        w.add_object(Sphere {
+            transform: BaseMatrix::identity().translate(0, 0, 10),
+            ..Default::default()
+        });
+        let r = Ray::new(point(0, 0, 5), vector(0, 0, 1));
+        let i = Intersection::new(4, w.objects()[1].as_ref());
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+        let c = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::new(0.1, 0.1, 0.1), c);
+    }
+
+    #[test]
+    fn the_reflected_color_for_a_nonreflective_material() {
+        let mut w = World::default();
+        w.objects[1] = Box::new(Sphere {
+            transform: BaseMatrix::identity().scale(0.5, 0.5, 0.5),
+            material: Material {
+                ambient: 1.,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let r = Ray::new(point(0, 0, 0), vector(0, 0, 1));
+        let i = Intersection::new(1, w.objects()[1].as_ref());
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+        let color = w.reflected_color(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::BLACK, color);
+    }
+
+    #[test]
+    fn the_reflected_color_for_a_reflective_material() {
+        let mut w = World::default();
+        w.add_object(Plane {
+            transform: BaseMatrix::identity().translate(0, -1, 0),
+            material: Material {
+                reflective: 0.5,
+                ..Default::default()
+            },
+        });
+        let r = Ray::new(point(0, 0, -3), vector(0, -SQRT_2 / 2., SQRT_2 / 2.));
+        let i = Intersection::new(SQRT_2, w.objects()[2].as_ref());
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+        let color = w.reflected_color(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::new(0.19033, 0.23791, 0.14274), color);
+    }
+
+    #[test]
+    fn shade_hit_with_a_reflective_material() {
+        let mut w = World::default();
+        w.add_object(Plane {
+            transform: BaseMatrix::identity().translate(0, -1, 0),
+            material: Material {
+                reflective: 0.5,
+                ..Default::default()
+            },
+        });
+        let r = Ray::new(point(0, 0, -3), vector(0, -SQRT_2 / 2., SQRT_2 / 2.));
+        let i = Intersection::new(SQRT_2, w.objects()[2].as_ref());
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+        let color = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::new(0.87676, 0.92434, 0.82917), color);
+    }
+
+    #[test]
+    fn color_at_with_mutually_reflective_surfaces() {
+        let mirror = Material {
+            reflective: 1.,
+            ..Default::default()
+        };
+        let mut w = World::new();
+        w.add_light(Light::new(point(0, 0, 0), Color::new(1, 1, 1)));
+        w.add_object(Plane {
+            transform: BaseMatrix::identity().translate(0, -1, 0),
+            material: mirror.clone(),
+        });
+        w.add_object(Plane {
+            transform: BaseMatrix::identity().translate(0, 1, 0),
+            material: mirror,
+        });
+        let r = Ray::new(point(0, 0, 0), vector(0, 1, 0));
+
+        // must terminate rather than bounce forever
+        let _ = w.color_at(r);
+    }
+
+    #[test]
+    fn the_reflected_color_at_the_maximum_recursive_depth() {
+        let mut w = World::default();
+        w.add_object(Plane {
+            transform: BaseMatrix::identity().translate(0, -1, 0),
+            material: Material {
+                reflective: 0.5,
+                ..Default::default()
+            },
+        });
+        let r = Ray::new(point(0, 0, -3), vector(0, -SQRT_2 / 2., SQRT_2 / 2.));
+        let i = Intersection::new(SQRT_2, w.objects()[2].as_ref());
+
+        let comps = prepare_computations(&i, &r, &Intersections::from(vec![i]));
+        let color = w.reflected_color(&comps, 0);
+
+        assert_eq!(Color::BLACK, color);
+    }
+
+    #[test]
+    fn the_refracted_color_with_an_opaque_surface() {
+        let w = World::default();
+        let shape = w.objects()[0].as_ref();
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let xs = Intersections::from(vec![
+            Intersection::new(4, shape),
+            Intersection::new(6, shape),
+        ]);
+
+        let comps = prepare_computations(&xs[0], &r, &xs);
+        let c = w.refracted_color(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::BLACK, c);
+    }
+
+    #[test]
+    fn the_refracted_color_at_the_maximum_recursive_depth() {
+        let mut w = World::default();
+        w.objects[0] = Box::new(Sphere {
+            material: Material {
+                transparency: 1.0,
+                refractive_index: 1.5,
+                ..w.objects()[0].material().clone()
+            },
+            ..Default::default()
+        });
+        let shape = w.objects()[0].as_ref();
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+        let xs = Intersections::from(vec![
+            Intersection::new(4, shape),
+            Intersection::new(6, shape),
+        ]);
+
+        let comps = prepare_computations(&xs[0], &r, &xs);
+        let c = w.refracted_color(&comps, 0);
+
+        assert_eq!(Color::BLACK, c);
+    }
+
+    #[test]
+    fn the_refracted_color_under_total_internal_reflection() {
+        let mut w = World::default();
+        w.objects[0] = Box::new(Sphere {
+            material: Material {
+                transparency: 1.0,
+                refractive_index: 1.5,
+                ..w.objects()[0].material().clone()
+            },
+            ..Default::default()
+        });
+        let shape = w.objects()[0].as_ref();
+        let r = Ray::new(point(0, 0, SQRT_2 / 2.), vector(0, 1, 0));
+        let xs = Intersections::from(vec![
+            Intersection::new(-SQRT_2 / 2., shape),
+            Intersection::new(SQRT_2 / 2., shape),
+        ]);
+
+        // inside the sphere, so look at the second intersection
+        let comps = prepare_computations(&xs[1], &r, &xs);
+        let c = w.refracted_color(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::BLACK, c);
+    }
+
+    #[test]
+    fn the_refracted_color_with_a_refracted_ray() {
+        let mut w = World::default();
+        w.objects[0] = Box::new(Sphere {
+            material: Material {
+                ambient: 1.0,
+                pattern: Some(Arc::new(TestPattern)),
+                ..w.objects()[0].material().clone()
+            },
+            ..Default::default()
+        });
+        w.objects[1] = Box::new(Sphere {
+            transform: BaseMatrix::identity().scale(0.5, 0.5, 0.5),
+            material: Material {
+                transparency: 1.0,
+                refractive_index: 1.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let (a, b) = (w.objects()[0].as_ref(), w.objects()[1].as_ref());
+        let r = Ray::new(point(0, 0, 0.1), vector(0, 1, 0));
+        let xs = Intersections::from(vec![
+            Intersection::new(-0.9899, a),
+            Intersection::new(-0.4899, b),
+            Intersection::new(0.4899, b),
+            Intersection::new(0.9899, a),
+        ]);
+
+        let comps = prepare_computations(&xs[2], &r, &xs);
+        let c = w.refracted_color(&comps, DEFAULT_MAX_DEPTH);
+
+        // the book has 0.04725, with points nudged ten times further off the surface
+        assert_eq!(Color::new(0, 0.99888, 0.04722), c);
+    }
+
+    #[test]
+    fn patterns_follow_the_objects_they_are_on() {
+        let mut w = World::new();
+        w.add_light(Light::new(point(0, 0, -10), Color::WHITE));
+        w.add_object(Sphere {
+            // the stripes are twice as wide on the scaled sphere, so x = 1.5 is in the
+            // first, white, stripe
+            transform: BaseMatrix::identity().scale(2, 2, 2),
+            material: Material {
+                pattern: Some(Arc::new(Stripe::new(Color::WHITE, Color::BLACK))),
+                ambient: 1.,
+                diffuse: 0.,
+                specular: 0.,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let r = Ray::new(point(1.5, 0, -10), vector(0, 0, 1));
+
+        assert_eq!(Color::WHITE, w.color_at(r));
+    }
+
+    #[test]
+    fn shade_hit_with_a_transparent_material() {
+        let mut w = World::default();
+        w.add_object(Plane {
+            transform: BaseMatrix::identity().translate(0, -1, 0),
+            material: Material {
+                transparency: 0.5,
+                refractive_index: 1.5,
+                ..Default::default()
+            },
+        });
+        w.add_object(Sphere {
+            transform: BaseMatrix::identity().translate(0, -3.5, -0.5),
+            material: Material {
+                color: Color::new(1, 0, 0),
+                ambient: 0.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let r = Ray::new(point(0, 0, -3), vector(0, -SQRT_2 / 2., SQRT_2 / 2.));
+        let xs = Intersections::from(vec![Intersection::new(SQRT_2, w.objects()[2].as_ref())]);
+
+        let comps = prepare_computations(&xs[0], &r, &xs);
+        let color = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::new(0.93642, 0.68642, 0.68642), color);
+    }
+
+    #[test]
+    fn shade_hit_with_a_reflective_transparent_material() {
+        let mut w = World::default();
+        w.add_object(Plane {
+            transform: BaseMatrix::identity().translate(0, -1, 0),
+            material: Material {
+                reflective: 0.5,
+                transparency: 0.5,
+                refractive_index: 1.5,
+                ..Default::default()
+            },
+        });
+        w.add_object(Sphere {
+            transform: BaseMatrix::identity().translate(0, -3.5, -0.5),
+            material: Material {
+                color: Color::new(1, 0, 0),
+                ambient: 0.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let r = Ray::new(point(0, 0, -3), vector(0, -SQRT_2 / 2., SQRT_2 / 2.));
+        let xs = Intersections::from(vec![Intersection::new(SQRT_2, w.objects()[2].as_ref())]);
+
+        let comps = prepare_computations(&xs[0], &r, &xs);
+        let color = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+
+        assert_eq!(Color::new(0.93391, 0.69643, 0.69243), color);
+    }
+
+    #[test]
+    fn a_world_can_be_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<World>();
+        assert_send_sync::<Sphere>();
+        assert_send_sync::<Material>();
+        assert_send_sync::<Light>();
+    }
+
+    #[test]
+    fn intersecting_a_world_through_its_hierarchy() {
+        for split in [BvhSplit::SurfaceArea, BvhSplit::Median] {
+            let mut w = World::new();
+            w.set_bvh_split(split);
+            for x in 0..10 {
+                for z in 0..10 {
+                    w.add_object(Sphere {
+                        transform: BaseMatrix::identity().translate(3 * x, 0, 3 * z),
+                        ..Default::default()
+                    });
+                }
+            }
+            w.add_object(Plane {
+                transform: BaseMatrix::identity().translate(0, -2, 0),
+                ..Default::default()
+            });
+            let r = Ray::new(point(12, 5, 21), vector(0, -1, 0));
+
+            let xs = w.intersect(r);
+
+            assert!(w.bvh().depth() > 1);
+            assert_eq!(vec![4., 6., 7.], xs.iter().map(|i| i.t).collect::<Vec<_>>());
+            assert!(std::ptr::addr_eq(xs[0].obj, w.objects()[47].as_ref()));
+            assert!(std::ptr::addr_eq(xs[2].obj, w.objects()[100].as_ref()));
+        }
+    }
+
+    #[test]
+    fn a_world_holds_objects_of_different_types() {
+        #[derive(Debug)]
+        struct Marker(Material);
+
+        impl Shape for Marker {
+            fn transform(&self) -> BaseMatrix<4> {
+                BaseMatrix::identity()
+            }
+
+            fn material(&self) -> &Material {
+                &self.0
+            }
+
+            fn local_intersect(&self, _ray: Ray) -> Option<Intersections<'_>> {
+                None
+            }
+
+            fn local_normal_at(&self, _at: Tuple) -> Tuple {
+                vector(0, 1, 0)
+            }
+        }
+
+        let mut w = World::default();
+        w.add_object(Marker(Material::default()));
+        let r = Ray::new(point(0, 0, -5), vector(0, 0, 1));
+
+        assert_eq!(3, w.size());
+        assert_eq!(4, w.intersect(r).len());
     }
 }
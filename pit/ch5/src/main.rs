@@ -1,6 +1,6 @@
 use apolaki_canvas::Canvas;
 use apolaki_color::Color;
-use apolaki_objects::{Intersect, Sphere};
+use apolaki_objects::{Shape, Sphere};
 use apolaki_ray::Ray;
 use apolaki_tuple::point;
 use std::fs::File;
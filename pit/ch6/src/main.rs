@@ -2,17 +2,20 @@ use apolaki_canvas::Canvas;
 use apolaki_color::Color;
 use apolaki_light::Light;
 use apolaki_material::Material;
-use apolaki_objects::{Intersect, Sphere};
+use apolaki_objects::{Shape, Sphere};
 use apolaki_ray::Ray;
 use apolaki_tuple::point;
 use std::fs::File;
 use std::io::Write;
 
 fn main() -> Result<(), std::io::Error> {
-    let mut s = Sphere::default();
-    let mut m = Material::default();
-    m.color = Color::new(1, 0.2, 1);
-    s.material = m;
+    let s = Sphere {
+        material: Material {
+            color: Color::new(1, 0.2, 1),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
     let light = Light::new(point(-10, 10, -10), Color::new(1, 1, 1));
     let canvas_pixels = 100;
     let mut canvas = Canvas::with_size(canvas_pixels, canvas_pixels);
@@ -36,7 +39,10 @@ fn main() -> Result<(), std::io::Error> {
                     let n = it.obj.normal_at(p);
                     let eye = -r.dir;
 
-                    let calculated_color = it.obj.material.lighting(&light, p, eye, n);
+                    let calculated_color =
+                        it.obj
+                            .material()
+                            .lighting(&light, p, it.world_to_object(p), eye, n, 1.0);
                     canvas.write(x, y, calculated_color);
                 }
             }
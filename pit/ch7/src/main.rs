@@ -0,0 +1,100 @@
+use apolaki_camera::{view_transform, Camera};
+use apolaki_color::Color;
+use apolaki_light::Light;
+use apolaki_material::Material;
+use apolaki_matrix::BaseMatrix;
+use apolaki_objects::Sphere;
+use apolaki_tuple::{point, vector};
+use apolaki_world::World;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+
+fn main() -> Result<(), std::io::Error> {
+    let wall_material = Material {
+        color: Color::new(1, 0.9, 0.9),
+        specular: 0.,
+        ..Default::default()
+    };
+
+    let floor = Sphere {
+        transform: BaseMatrix::identity().scale(10, 0.01, 10),
+        material: wall_material.clone(),
+        ..Default::default()
+    };
+
+    let left_wall = Sphere {
+        transform: BaseMatrix::identity()
+            .scale(10, 0.01, 10)
+            .rotate_x(PI / 2.)
+            .rotate_y(-PI / 4.)
+            .translate(0, 0, 5),
+        material: wall_material.clone(),
+        ..Default::default()
+    };
+
+    let right_wall = Sphere {
+        transform: BaseMatrix::identity()
+            .scale(10, 0.01, 10)
+            .rotate_x(PI / 2.)
+            .rotate_y(PI / 4.)
+            .translate(0, 0, 5),
+        material: wall_material,
+        ..Default::default()
+    };
+
+    let middle = Sphere {
+        transform: BaseMatrix::identity().translate(-0.5, 1, 0.5),
+        material: Material {
+            color: Color::new(0.1, 1, 0.5),
+            diffuse: 0.7,
+            specular: 0.3,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let right = Sphere {
+        transform: BaseMatrix::identity()
+            .scale(0.5, 0.5, 0.5)
+            .translate(1.5, 0.5, -0.5),
+        material: Material {
+            color: Color::new(0.5, 1, 0.1),
+            diffuse: 0.7,
+            specular: 0.3,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let left = Sphere {
+        transform: BaseMatrix::identity()
+            .scale(0.33, 0.33, 0.33)
+            .translate(-1.5, 0.33, -0.75),
+        material: Material {
+            color: Color::new(1, 0.8, 0.1),
+            diffuse: 0.7,
+            specular: 0.3,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut world = World::new();
+    world.add_object(floor);
+    world.add_object(left_wall);
+    world.add_object(right_wall);
+    world.add_object(middle);
+    world.add_object(right);
+    world.add_object(left);
+    world.add_light(Light::new(point(-10, 10, -10), Color::new(1, 1, 1)));
+
+    let mut camera = Camera::new(100, 50, PI / 3.);
+    camera.transform = view_transform(point(0, 1.5, -5), point(0, 1, 0), vector(0, 1, 0));
+
+    let canvas = camera.render(&world);
+
+    let ppm = canvas.to_ppm_string();
+    let mut f = File::create("pit7.ppm")?;
+    f.write_all(ppm.as_bytes())
+}